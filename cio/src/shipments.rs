@@ -13,7 +13,7 @@ use reqwest::StatusCode;
 use schemars::JsonSchema;
 use sendgrid_api::{traits::MailOps, Client as SendGrid};
 use serde::{Deserialize, Serialize};
-use shippo::{Address, CustomsDeclaration, CustomsItem, NewShipment, NewTransaction, Parcel, Shippo};
+use shippo::{Address, Carrier, CustomsDeclaration, CustomsItem, NewShipment, NewTransaction, Parcel, Shippo};
 use slack_chat_api::{
     FormattedMessage, MessageAttachment, MessageBlock, MessageBlockText, MessageBlockType, MessageType,
 };
@@ -112,7 +112,9 @@ impl NewInboundShipment {
         }
 
         // Get the tracking status for the shipment and fill in the details.
-        let ts = shippo.get_tracking_status(&carrier, &self.tracking_number).await?;
+        let ts = shippo
+            .get_tracking_status(&Carrier::from(carrier.as_str()), &self.tracking_number)
+            .await?;
         self.tracking_number = ts.tracking_number.to_string();
         let mut status = ts.tracking_status.unwrap_or_default();
         self.tracking_link();
@@ -152,7 +154,7 @@ impl NewInboundShipment {
 
         // Register a tracking webhook for this shipment.
         shippo
-            .register_tracking_webhook(&carrier, &self.tracking_number)
+            .register_tracking_webhook(&Carrier::from(carrier.as_str()), &self.tracking_number)
             .await?;
 
         // Set the new status.
@@ -1180,7 +1182,9 @@ The Shipping Bot",
         }
 
         // Get the tracking status for the shipment and fill in the details.
-        let ts = shippo.get_tracking_status(&carrier, &self.tracking_number).await?;
+        let ts = shippo
+            .get_tracking_status(&Carrier::from(carrier.as_str()), &self.tracking_number)
+            .await?;
         self.tracking_number = ts.tracking_number.to_string();
         let mut status = ts.tracking_status.unwrap_or_default();
         self.eta = ts.eta;
@@ -1219,7 +1223,7 @@ The Shipping Bot",
 
         // Register a tracking webhook for this shipment.
         shippo
-            .register_tracking_webhook(&carrier, &self.tracking_number)
+            .register_tracking_webhook(&Carrier::from(carrier.as_str()), &self.tracking_number)
             .await?;
 
         // Set the new status.
@@ -1280,7 +1284,7 @@ The Shipping Bot",
 
             // Register a tracking webhook for this shipment.
             match shippo_client
-                .register_tracking_webhook(&self.carrier, &self.tracking_number)
+                .register_tracking_webhook(&Carrier::from(self.carrier.as_str()), &self.tracking_number)
                 .await
             {
                 Ok(status) => {
@@ -1427,6 +1431,8 @@ The Shipping Bot",
                     test: Default::default(),
                 }],
                 customs_declaration: cd,
+                extra: None,
+                r#async: None,
             })
             .await?;
 
@@ -1442,6 +1448,7 @@ The Shipping Bot",
                         r#async: false,
                         label_file_type: "".to_string(),
                         metadata: "".to_string(),
+                        test: None,
                     })
                     .await?;
 
@@ -1471,7 +1478,7 @@ The Shipping Bot",
 
                 // Register a tracking webhook for this shipment.
                 shippo_client
-                    .register_tracking_webhook(&self.carrier, &self.tracking_number)
+                    .register_tracking_webhook(&Carrier::from(self.carrier.as_str()), &self.tracking_number)
                     .await?;
 
                 // Print the label.
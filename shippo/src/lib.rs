@@ -24,22 +24,41 @@
  * ```
  */
 #![allow(clippy::field_reassign_with_default)]
-use std::{borrow::Cow, collections::HashMap, env, error, fmt, fmt::Debug, str::FromStr, sync::Arc};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    convert::Infallible,
+    env, error, fmt,
+    fmt::Debug,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use chrono::{offset::Utc, DateTime};
+use chrono::{offset::Utc, DateTime, SecondsFormat};
+use futures::{
+    future,
+    stream::{self, Stream, StreamExt},
+};
 use reqwest::{header, Client, Method, Request, StatusCode, Url};
 use schemars::JsonSchema;
 use serde::{
-    de::{self, Visitor},
+    de::{self, DeserializeOwned, Visitor},
     Deserialize, Serialize,
 };
 
 /// Endpoint for the Shippo API.
 const ENDPOINT: &str = "https://api.goshippo.com/";
 
+/// The prefix Shippo uses for test API tokens.
+const TEST_TOKEN_PREFIX: &str = "shippo_test_";
+
 /// Entrypoint for interacting with the Shippo API.
 pub struct Shippo {
     token: String,
+    base_url: Url,
+    test_mode: bool,
+    validate_rates: bool,
 
     client: Arc<Client>,
 }
@@ -56,6 +75,9 @@ impl Shippo {
         match client {
             Ok(c) => Self {
                 token: token.to_string(),
+                base_url: Url::parse(ENDPOINT).unwrap(),
+                test_mode: false,
+                validate_rates: false,
 
                 client: Arc::new(c),
             },
@@ -73,12 +95,53 @@ impl Shippo {
         Shippo::new(token)
     }
 
+    /// Set the base url for `Shippo`
+    pub fn base_url(mut self, base_url: Url) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    /// Put `Shippo` in test mode.
+    ///
+    /// In test mode any attempt to purchase a label (create a transaction) with
+    /// a live token fails with an error before a request is sent, so a script
+    /// meant for testing can never buy a real label. Test tokens, which start
+    /// with `shippo_test_`, are unaffected.
+    pub fn test_mode(mut self, test_mode: bool) -> Self {
+        self.test_mode = test_mode;
+        self
+    }
+
+    /// Validate the rate before purchasing a label from it.
+    ///
+    /// When enabled, `create_shipping_label_from_rate` first fetches the rate
+    /// and checks it with `NewTransaction::validate_against`, warning if the
+    /// rate's `test` flag does not match the kind of token we have. This costs
+    /// an extra request per purchase but turns Shippo's confusing errors for
+    /// expired rates into clear ones.
+    pub fn validate_rates(mut self, validate_rates: bool) -> Self {
+        self.validate_rates = validate_rates;
+        self
+    }
+
+    /// Returns an error if we are in test mode but have a live token, in which
+    /// case purchases would be real.
+    fn check_purchase_allowed(&self) -> Result<(), APIError> {
+        if self.test_mode && !self.token.starts_with(TEST_TOKEN_PREFIX) {
+            return Err(APIError {
+                status_code: StatusCode::FORBIDDEN,
+                body: "refusing to purchase a label with a live token in test mode".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
     fn request<B>(&self, method: Method, path: &str, body: B, query: Option<Vec<(String, String)>>) -> Request
     where
         B: Serialize,
     {
-        let base = Url::parse(ENDPOINT).unwrap();
-        let url = base.join(path).unwrap();
+        let url = self.base_url.join(path).unwrap();
 
         let bt = format!("ShippoToken {}", self.token);
         let bearer = header::HeaderValue::from_str(&bt).unwrap();
@@ -109,6 +172,15 @@ impl Shippo {
         rb.build().unwrap()
     }
 
+    /// Send a request. A failure to send it, or to get a response, is returned
+    /// as an `APIError` with the reason in the body.
+    async fn execute(&self, request: Request) -> Result<reqwest::Response, APIError> {
+        self.client.execute(request).await.map_err(|e| APIError {
+            status_code: e.status().unwrap_or(StatusCode::SERVICE_UNAVAILABLE),
+            body: format!("sending request failed: {e}"),
+        })
+    }
+
     /// List shipments.
     /// FROM: https://goshippo.com/docs/reference#shipments-list
     /// A maximum date range of 90 days is permitted. Provided dates should be ISO 8601 UTC dates.
@@ -117,92 +189,108 @@ impl Shippo {
         // TODO: paginate.
         let request = self.request(Method::GET, "shipments", (), None);
 
-        let resp = self.client.execute(request).await.unwrap();
+        let resp = self.execute(request).await?;
         match resp.status() {
             StatusCode::OK => (),
-            s => {
-                return Err(APIError {
-                    status_code: s,
-                    body: resp.text().await.unwrap(),
-                })
-            }
+            _ => return Err(error_response(resp).await),
         };
 
-        let r: APIResponse = resp.json().await.unwrap();
+        let r: APIResponse = parse_json(resp).await?;
 
         Ok(r.shipments)
     }
 
+    /// List the shipments created between `start` and `end`.
+    /// FROM: https://goshippo.com/docs/reference#shipments-list
+    /// A maximum date range of 90 days is permitted, larger ranges are rejected
+    /// before making a request.
+    pub async fn list_shipments_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<Shipment>, APIError> {
+        let query = shipments_created_between_query(start, end)?;
+
+        self.list_paginated("shipments", Some(query)).await
+    }
+
     /// Create a shipment.
     /// FROM: https://goshippo.com/docs/reference#shipments-create
     pub async fn create_shipment(&self, ns: NewShipment) -> Result<Shipment, APIError> {
         // Build the request.
         let request = self.request(Method::POST, "shipments", ns, None);
 
-        let resp = self.client.execute(request).await.unwrap();
+        let resp = self.execute(request).await?;
         match resp.status() {
             StatusCode::CREATED => (),
-            s => {
-                return Err(APIError {
-                    status_code: s,
-                    body: resp.text().await.unwrap(),
-                })
-            }
+            _ => return Err(error_response(resp).await),
         };
 
-        Ok(resp.json().await.unwrap())
+        parse_json(resp).await
     }
 
     /// List the orders.
     /// FROM: https://goshippo.com/docs/reference#orders-list
     pub async fn list_orders(&self) -> Result<Vec<Order>, APIError> {
+        self.list_paginated("orders", None).await
+    }
+
+    /// Create an order.
+    /// FROM: https://goshippo.com/docs/reference#orders-create
+    pub async fn create_order(&self, order: NewOrder) -> Result<Order, APIError> {
         // Build the request.
-        let mut request = self.request(Method::GET, "orders", (), None);
+        let request = self.request(Method::POST, "orders/", order, None);
 
-        let mut resp = self.client.execute(request).await.unwrap();
+        let resp = self.execute(request).await?;
+        match resp.status() {
+            StatusCode::CREATED => (),
+            _ => return Err(error_response(resp).await),
+        };
+
+        parse_json(resp).await
+    }
+
+    /// Get an order.
+    /// FROM: https://goshippo.com/docs/reference#orders-retrieve
+    pub async fn get_order(&self, id: &str) -> Result<Order, APIError> {
+        // Build the request.
+        let request = self.request(Method::GET, &format!("orders/{id}"), (), None);
+
+        let resp = self.execute(request).await?;
         match resp.status() {
             StatusCode::OK => (),
-            s => {
-                return Err(APIError {
-                    status_code: s,
-                    body: resp.text().await.unwrap(),
-                })
-            }
+            _ => return Err(error_response(resp).await),
         };
 
-        let mut r: OrdersAPIResponse = resp.json().await.unwrap();
-        let mut orders = r.orders;
-        let mut page = r.next;
+        parse_json(resp).await
+    }
 
-        // Paginate if we should.
-        // TODO: make this more DRY
-        while !page.is_empty() {
-            let url = Url::parse(&page).unwrap();
-            let pairs: Vec<(Cow<'_, str>, Cow<'_, str>)> = url.query_pairs().collect();
-            let mut new_pairs: Vec<(String, String)> = Vec::new();
-            for (a, b) in pairs {
-                let sa = a.into_owned();
-                let sb = b.into_owned();
-                new_pairs.push((sa, sb));
-            }
+    /// List the carrier accounts.
+    /// FROM: https://goshippo.com/docs/reference#carrier-accounts
+    pub async fn list_carrier_accounts(&self) -> Result<Vec<CarrierAccount>, APIError> {
+        self.list_paginated("carrier_accounts", None).await
+    }
 
-            request = self.request(Method::GET, "orders", (), Some(new_pairs));
+    /// Stream the orders, fetching each page as the previous one is consumed.
+    /// FROM: https://goshippo.com/docs/reference#orders-list
+    pub fn stream_orders(&self) -> impl Stream<Item = Result<Order, APIError>> + '_ {
+        self.stream_paginated("orders")
+    }
 
-            resp = self.client.execute(request).await.unwrap();
-            match resp.status() {
-                StatusCode::OK => (),
-                s => {
-                    return Err(APIError {
-                        status_code: s,
-                        body: resp.text().await.unwrap(),
-                    })
-                }
-            };
+    /// List all the objects at a paginated endpoint, following the `next` links.
+    async fn list_paginated<T>(&self, path: &str, query: Option<Vec<(String, String)>>) -> Result<Vec<T>, APIError>
+    where
+        T: DeserializeOwned,
+    {
+        let mut r: PaginatedResponse<T> = self.get_page(path, query).await?;
+        let mut results = r.results;
+        let mut page = r.next;
 
-            // Try to deserialize the response.
-            r = resp.json().await.unwrap();
+        // Paginate if we should.
+        while !page.is_empty() {
+            r = self.get_page(path, Some(next_page_query(&page)?)).await?;
 
-            orders.append(&mut r.orders);
+            results.append(&mut r.results);
 
             if !r.next.is_empty() && r.next != page {
                 page = r.next;
@@ -211,68 +299,136 @@ impl Shippo {
             }
         }
 
-        Ok(orders)
+        Ok(results)
     }
 
-    /// List the carrier accounts.
-    /// FROM: https://goshippo.com/docs/reference#carrier-accounts
-    pub async fn list_carrier_accounts(&self) -> Result<Vec<CarrierAccount>, APIError> {
+    /// Stream all the objects at a paginated endpoint, following the `next`
+    /// links. The next page is only fetched once the current one is consumed.
+    fn stream_paginated<T>(&self, path: &str) -> impl Stream<Item = Result<T, APIError>> + '_
+    where
+        T: DeserializeOwned + 'static,
+    {
+        let path = path.to_string();
+
+        // The state is the `next` link of the page to fetch, empty for the first
+        // page, or `None` once we are done.
+        stream::unfold(Some(String::new()), move |page| {
+            let path = path.clone();
+            async move {
+                let page = page?;
+                let query = if page.is_empty() {
+                    None
+                } else {
+                    match next_page_query(&page) {
+                        Ok(query) => Some(query),
+                        Err(e) => return Some((stream::once(future::ready(Err(e))).right_stream(), None)),
+                    }
+                };
+
+                match self.get_page::<T>(&path, query).await {
+                    Ok(r) => {
+                        // Guard against the API handing us back the same page.
+                        let next = if !r.next.is_empty() && r.next != page {
+                            Some(r.next)
+                        } else {
+                            None
+                        };
+
+                        Some((stream::iter(r.results.into_iter().map(Ok)).left_stream(), next))
+                    }
+                    Err(e) => Some((stream::once(future::ready(Err(e))).right_stream(), None)),
+                }
+            }
+        })
+        .flatten()
+    }
+
+    /// Get a single page of a paginated endpoint.
+    async fn get_page<T>(
+        &self,
+        path: &str,
+        query: Option<Vec<(String, String)>>,
+    ) -> Result<PaginatedResponse<T>, APIError>
+    where
+        T: DeserializeOwned,
+    {
         // Build the request.
-        let mut request = self.request(Method::GET, "carrier_accounts", (), None);
+        let request = self.request(Method::GET, path, (), query);
 
-        let mut resp = self.client.execute(request).await.unwrap();
+        let resp = self.execute(request).await?;
         match resp.status() {
             StatusCode::OK => (),
-            s => {
-                return Err(APIError {
-                    status_code: s,
-                    body: resp.text().await.unwrap(),
-                })
-            }
+            _ => return Err(error_response(resp).await),
         };
 
-        let mut r: CarrierAccountsAPIResponse = resp.json().await.unwrap();
-        let mut carrier_accounts = r.carrier_accounts;
-        let mut page = r.next;
-
-        // Paginate if we should.
-        // TODO: make this more DRY
-        while !page.is_empty() {
-            let url = Url::parse(&page).unwrap();
-            let pairs: Vec<(Cow<'_, str>, Cow<'_, str>)> = url.query_pairs().collect();
-            let mut new_pairs: Vec<(String, String)> = Vec::new();
-            for (a, b) in pairs {
-                let sa = a.into_owned();
-                let sb = b.into_owned();
-                new_pairs.push((sa, sb));
-            }
-
-            request = self.request(Method::GET, "carrier_accounts", (), Some(new_pairs));
-
-            resp = self.client.execute(request).await.unwrap();
-            match resp.status() {
-                StatusCode::OK => (),
-                s => {
-                    return Err(APIError {
-                        status_code: s,
-                        body: resp.text().await.unwrap(),
-                    })
-                }
-            };
+        parse_json(resp).await
+    }
 
-            // Try to deserialize the response.
-            r = resp.json().await.unwrap();
+    /// Get the rates to send a parcel between two addresses.
+    ///
+    /// This creates a shipment synchronously, so the rates are returned right
+    /// away. If Shippo could not create the shipment the carrier messages are
+    /// returned in the error.
+    pub async fn shop_rates(&self, from: Address, to: Address, parcel: Parcel) -> Result<Vec<Rate>, APIError> {
+        let shipment = self
+            .create_shipment(NewShipment {
+                address_from: from,
+                address_to: to,
+                parcels: vec![parcel],
+                r#async: Some(false),
+                ..Default::default()
+            })
+            .await?;
+
+        if shipment.status == "ERROR" {
+            let messages: Vec<String> = shipment
+                .messages
+                .iter()
+                .map(|m| format!("{}: {}", m.source, m.text))
+                .collect();
+
+            return Err(APIError {
+                status_code: StatusCode::UNPROCESSABLE_ENTITY,
+                body: format!("shipment {} failed: {}", shipment.object_id, messages.join("; ")),
+            });
+        }
 
-            carrier_accounts.append(&mut r.carrier_accounts);
+        Ok(shipment.rates)
+    }
 
-            if !r.next.is_empty() && r.next != page {
-                page = r.next;
-            } else {
-                page = "".to_string();
-            }
+    /// Create a multi-piece shipment, a single shipment made up of several parcels.
+    ///
+    /// The rates returned on the shipment are for all the parcels together,
+    /// not per parcel. Purchasing one of those rates produces a label for each
+    /// parcel, in the same order as `parcels`. Not every carrier supports
+    /// multi-piece shipments; those that do not will return no rates.
+    /// FROM: https://goshippo.com/docs/multipiece
+    pub async fn create_multipiece_shipment(
+        &self,
+        from: Address,
+        to: Address,
+        parcels: Vec<Parcel>,
+    ) -> Result<Shipment, APIError> {
+        if parcels.is_empty() {
+            return Err(APIError {
+                status_code: StatusCode::BAD_REQUEST,
+                body: "a multi-piece shipment needs at least one parcel".to_string(),
+            });
         }
 
-        Ok(carrier_accounts)
+        self.create_shipment(NewShipment {
+            address_from: from,
+            address_to: to,
+            parcels,
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Create a return shipment for an existing shipment.
+    /// FROM: https://goshippo.com/docs/shipments/#return-labels
+    pub async fn create_return_shipment(&self, original: &Shipment) -> Result<Shipment, APIError> {
+        self.create_shipment(NewShipment::return_for(original)).await
     }
 
     /// Get a shipment.
@@ -281,18 +437,22 @@ impl Shippo {
         // Build the request.
         let request = self.request(Method::GET, &format!("shipments/{id}"), (), None);
 
-        let resp = self.client.execute(request).await.unwrap();
+        let resp = self.execute(request).await?;
         match resp.status() {
             StatusCode::OK => (),
-            s => {
-                return Err(APIError {
-                    status_code: s,
-                    body: resp.text().await.unwrap(),
-                })
-            }
+            _ => return Err(error_response(resp).await),
         };
 
-        Ok(resp.json().await.unwrap())
+        parse_json(resp).await
+    }
+
+    /// Get the cheapest rate for a shipment, across all the carrier accounts
+    /// that returned rates. If `max_days` is set, only rates with an estimated
+    /// transit time of at most that many days are considered.
+    pub async fn cheapest_rate(&self, shipment_id: &str, max_days: Option<i64>) -> Result<Option<Rate>, APIError> {
+        let shipment = self.get_shipment(shipment_id).await?;
+
+        Ok(cheapest_of(&shipment.rates, max_days))
     }
 
     /// Get a rate.
@@ -301,18 +461,13 @@ impl Shippo {
         // Build the request.
         let request = self.request(Method::GET, &format!("rates/{id}"), (), None);
 
-        let resp = self.client.execute(request).await.unwrap();
+        let resp = self.execute(request).await?;
         match resp.status() {
             StatusCode::OK => (),
-            s => {
-                return Err(APIError {
-                    status_code: s,
-                    body: resp.text().await.unwrap(),
-                })
-            }
+            _ => return Err(error_response(resp).await),
         };
 
-        Ok(resp.json().await.unwrap())
+        parse_json(resp).await
     }
 
     /// Create a pickup.
@@ -321,18 +476,44 @@ impl Shippo {
         // Build the request.
         let request = self.request(Method::POST, "pickups/", np, None);
 
-        let resp = self.client.execute(request).await.unwrap();
+        let resp = self.execute(request).await?;
         match resp.status() {
             StatusCode::CREATED => (),
-            s => {
-                return Err(APIError {
-                    status_code: s,
-                    body: resp.text().await.unwrap(),
-                })
-            }
+            _ => return Err(error_response(resp).await),
         };
 
-        Ok(resp.json().await.unwrap())
+        parse_json(resp).await
+    }
+
+    /// Create a parcel. Parcels can be created once and then referenced by
+    /// `object_id` when creating shipments, e.g. for standard box sizes.
+    /// FROM: https://goshippo.com/docs/reference#parcels-create
+    pub async fn create_parcel(&self, parcel: Parcel) -> Result<Parcel, APIError> {
+        // Build the request.
+        let request = self.request(Method::POST, "parcels/", parcel, None);
+
+        let resp = self.execute(request).await?;
+        match resp.status() {
+            StatusCode::CREATED => (),
+            _ => return Err(error_response(resp).await),
+        };
+
+        parse_json(resp).await
+    }
+
+    /// Get a parcel.
+    /// FROM: https://goshippo.com/docs/reference#parcels-retrieve
+    pub async fn get_parcel(&self, id: &str) -> Result<Parcel, APIError> {
+        // Build the request.
+        let request = self.request(Method::GET, &format!("parcels/{id}"), (), None);
+
+        let resp = self.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            _ => return Err(error_response(resp).await),
+        };
+
+        parse_json(resp).await
     }
 
     /// Create a customs item.
@@ -341,38 +522,97 @@ impl Shippo {
         // Build the request.
         let request = self.request(Method::POST, "customs/items/", c, None);
 
-        let resp = self.client.execute(request).await.unwrap();
+        let resp = self.execute(request).await?;
         match resp.status() {
             StatusCode::CREATED => (),
-            s => {
-                return Err(APIError {
-                    status_code: s,
-                    body: resp.text().await.unwrap(),
-                })
-            }
+            _ => return Err(error_response(resp).await),
+        };
+
+        parse_json(resp).await
+    }
+
+    /// Create a customs declaration.
+    /// FROM: https://goshippo.com/docs/reference#customs-declarations-create
+    pub async fn create_customs_declaration(&self, cd: CustomsDeclaration) -> Result<CustomsDeclaration, APIError> {
+        // Build the request.
+        let request = self.request(Method::POST, "customs/declarations/", cd, None);
+
+        let resp = self.execute(request).await?;
+        match resp.status() {
+            StatusCode::CREATED => (),
+            _ => return Err(error_response(resp).await),
+        };
+
+        parse_json(resp).await
+    }
+
+    /// Get a customs declaration.
+    /// FROM: https://goshippo.com/docs/reference#customs-declarations-retrieve
+    pub async fn get_customs_declaration(&self, id: &str) -> Result<CustomsDeclaration, APIError> {
+        // Build the request.
+        let request = self.request(Method::GET, &format!("customs/declarations/{id}"), (), None);
+
+        let resp = self.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            _ => return Err(error_response(resp).await),
+        };
+
+        parse_json(resp).await
+    }
+
+    /// Get a customs item.
+    /// FROM: https://goshippo.com/docs/reference#customs-items-retrieve
+    pub async fn get_customs_item(&self, id: &str) -> Result<CustomsItem, APIError> {
+        // Build the request.
+        let request = self.request(Method::GET, &format!("customs/items/{id}"), (), None);
+
+        let resp = self.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            _ => return Err(error_response(resp).await),
         };
 
-        Ok(resp.json().await.unwrap())
+        parse_json(resp).await
+    }
+
+    /// List the customs items.
+    /// FROM: https://goshippo.com/docs/reference#customs-items-list
+    pub async fn list_customs_items(&self) -> Result<Vec<CustomsItem>, APIError> {
+        self.list_paginated("customs/items/", None).await
     }
 
     /// Create a shipping label based on a rate.
+    /// In test mode this fails without sending a request unless the token is a test token.
     /// FROM: https://goshippo.com/docs/reference#transactions-create
     pub async fn create_shipping_label_from_rate(&self, nt: NewTransaction) -> Result<Transaction, APIError> {
+        self.check_purchase_allowed()?;
+
+        if self.validate_rates {
+            let rate = self.get_rate(&nt.rate).await?;
+
+            // Unless told otherwise, the label is a test label if we have a test token.
+            let nt = NewTransaction {
+                test: nt.test.or_else(|| Some(self.token.starts_with(TEST_TOKEN_PREFIX))),
+                ..nt
+            };
+
+            nt.validate_against(&rate).map_err(|e| APIError {
+                status_code: StatusCode::BAD_REQUEST,
+                body: e,
+            })?;
+        }
+
         // Build the request.
         let request = self.request(Method::POST, "transactions", nt, None);
 
-        let resp = self.client.execute(request).await.unwrap();
+        let resp = self.execute(request).await?;
         match resp.status() {
             StatusCode::CREATED => (),
-            s => {
-                return Err(APIError {
-                    status_code: s,
-                    body: resp.text().await.unwrap(),
-                })
-            }
+            _ => return Err(error_response(resp).await),
         };
 
-        Ok(resp.json().await.unwrap())
+        parse_json(resp).await
     }
 
     /// Get a shipping label.
@@ -381,28 +621,60 @@ impl Shippo {
         // Build the request.
         let request = self.request(Method::GET, &format!("transactions/{id}"), (), None);
 
-        let resp = self.client.execute(request).await.unwrap();
+        let resp = self.execute(request).await?;
         match resp.status() {
             StatusCode::OK => (),
-            s => {
-                return Err(APIError {
-                    status_code: s,
-                    body: resp.text().await.unwrap(),
-                })
-            }
+            _ => return Err(error_response(resp).await),
         };
 
-        let status = &resp.status();
+        parse_json(resp).await
+    }
 
-        match &resp.json::<Transaction>().await {
-            Ok(v) => Ok(v.clone()),
-            Err(e) => {
-                Err(APIError {
-                    status_code: *status,
-                    // TODO: somehow get the body
-                    body: format!("{e}"),
-                })
+    /// Wait for a shipment to finish processing, polling with backoff until
+    /// its status is no longer "WAITING" or "QUEUED" or the timeout elapses.
+    /// A shipment that ends in "ERROR" is returned as is, callers should check
+    /// the status.
+    pub async fn wait_for_shipment(&self, id: &str, timeout: Duration) -> Result<Shipment, APIError> {
+        let start = Instant::now();
+        let mut delay = POLL_INITIAL_DELAY;
+
+        loop {
+            let shipment = self.get_shipment(id).await?;
+            if !is_pending_status(&shipment.status) {
+                return Ok(shipment);
             }
+
+            delay = poll_backoff(start, timeout, delay).await.ok_or_else(|| APIError {
+                status_code: StatusCode::REQUEST_TIMEOUT,
+                body: format!(
+                    "timed out after {timeout:?} waiting for shipment {id}, last status: {}",
+                    shipment.status
+                ),
+            })?;
+        }
+    }
+
+    /// Wait for a transaction (shipping label) to finish processing, polling
+    /// with backoff until its status is no longer "WAITING" or "QUEUED" or the
+    /// timeout elapses. A transaction that ends in "ERROR" is returned as is,
+    /// callers should check the status and messages.
+    pub async fn wait_for_transaction(&self, id: &str, timeout: Duration) -> Result<Transaction, APIError> {
+        let start = Instant::now();
+        let mut delay = POLL_INITIAL_DELAY;
+
+        loop {
+            let transaction = self.get_shipping_label(id).await?;
+            if !is_pending_status(&transaction.status) {
+                return Ok(transaction);
+            }
+
+            delay = poll_backoff(start, timeout, delay).await.ok_or_else(|| APIError {
+                status_code: StatusCode::REQUEST_TIMEOUT,
+                body: format!(
+                    "timed out after {timeout:?} waiting for transaction {id}, last status: {}",
+                    transaction.status
+                ),
+            })?;
         }
     }
 
@@ -413,18 +685,13 @@ impl Shippo {
         // TODO: paginate.
         let request = self.request(Method::GET, "transactions", (), None);
 
-        let resp = self.client.execute(request).await.unwrap();
+        let resp = self.execute(request).await?;
         match resp.status() {
             StatusCode::OK => (),
-            s => {
-                return Err(APIError {
-                    status_code: s,
-                    body: resp.text().await.unwrap(),
-                })
-            }
+            _ => return Err(error_response(resp).await),
         };
 
-        let r: TransactionsAPIResponse = resp.json().await.unwrap();
+        let r: TransactionsAPIResponse = parse_json(resp).await?;
 
         Ok(r.transactions)
     }
@@ -436,49 +703,92 @@ impl Shippo {
     /// FROM: https://goshippo.com/docs/reference#tracks-create
     pub async fn register_tracking_webhook(
         &self,
-        carrier: &str,
+        carrier: &Carrier,
         tracking_number: &str,
     ) -> Result<TrackingStatus, APIError> {
         let mut body: HashMap<&str, &str> = HashMap::new();
         body.insert("tracking_number", tracking_number);
-        body.insert("carrier", carrier);
+        body.insert("carrier", carrier.as_str());
 
         // Build the request
         let request = self.request(Method::POST, "tracks", body, None);
 
-        let resp = self.client.execute(request).await.unwrap();
+        let resp = self.execute(request).await?;
         match resp.status() {
             StatusCode::OK => (),
             StatusCode::CREATED => (),
-            s => {
-                return Err(APIError {
-                    status_code: s,
-                    body: resp.text().await.unwrap(),
-                })
-            }
+            _ => return Err(error_response(resp).await),
         };
 
-        Ok(resp.json().await.unwrap_or_default())
+        Ok(parse_json_or_default(resp).await)
     }
 
     /// Request the tracking status of a shipment by sending a GET request.
     /// FROM: https://goshippo.com/docs/reference#tracks-retrieve
-    pub async fn get_tracking_status(&self, carrier: &str, tracking_number: &str) -> Result<TrackingStatus, APIError> {
+    pub async fn get_tracking_status(
+        &self,
+        carrier: &Carrier,
+        tracking_number: &str,
+    ) -> Result<TrackingStatus, APIError> {
         // Build the request
         let request = self.request(Method::GET, &format!("tracks/{carrier}/{tracking_number}"), (), None);
 
-        let resp = self.client.execute(request).await.unwrap();
+        let resp = self.execute(request).await?;
         match resp.status() {
             StatusCode::OK => (),
-            s => {
-                return Err(APIError {
-                    status_code: s,
-                    body: resp.text().await.unwrap(),
-                })
-            }
+            _ => return Err(error_response(resp).await),
+        };
+
+        Ok(parse_json_or_default(resp).await)
+    }
+
+    /// List the webhooks.
+    /// FROM: https://goshippo.com/docs/reference#webhooks-list
+    pub async fn list_webhooks(&self) -> Result<Vec<Webhook>, APIError> {
+        self.list_paginated("webhooks", None).await
+    }
+
+    /// Create a webhook for an event, for example "track_updated" or
+    /// "transaction_created".
+    /// FROM: https://goshippo.com/docs/reference#webhooks-create
+    pub async fn create_webhook(&self, url: &str, event: &str) -> Result<Webhook, APIError> {
+        // Build the request.
+        let request = self.request(
+            Method::POST,
+            "webhooks",
+            NewWebhook {
+                url: url.to_string(),
+                event: event.to_string(),
+                active: true,
+                is_test: false,
+            },
+            None,
+        );
+
+        let resp = self.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            StatusCode::CREATED => (),
+            _ => return Err(error_response(resp).await),
+        };
+
+        parse_json(resp).await
+    }
+
+    /// Delete a webhook.
+    /// FROM: https://goshippo.com/docs/reference#webhooks-delete
+    pub async fn delete_webhook(&self, id: &str) -> Result<(), APIError> {
+        // Build the request.
+        let request = self.request(Method::DELETE, &format!("webhooks/{id}"), (), None);
+
+        let resp = self.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            StatusCode::NO_CONTENT => (),
+            _ => return Err(error_response(resp).await),
         };
 
-        Ok(resp.json().await.unwrap_or_default())
+        Ok(())
     }
 }
 
@@ -516,6 +826,156 @@ impl error::Error for APIError {
     }
 }
 
+/// Build the error for a response with an unexpected status code, keeping the
+/// raw body so callers can see what Shippo said.
+async fn error_response(resp: reqwest::Response) -> APIError {
+    let status_code = resp.status();
+    let body = match resp.text().await {
+        Ok(body) => body,
+        Err(e) => format!("reading response body failed: {e}"),
+    };
+
+    APIError { status_code, body }
+}
+
+/// Deserialize a response body. If that fails the error includes the path to
+/// the field that did not match our types and the raw body, rather than
+/// panicking.
+async fn parse_json<T>(resp: reqwest::Response) -> Result<T, APIError>
+where
+    T: DeserializeOwned,
+{
+    let status_code = resp.status();
+    let body = resp.text().await.map_err(|e| APIError {
+        status_code,
+        body: format!("reading response body failed: {e}"),
+    })?;
+
+    parse_json_body(status_code, &body)
+}
+
+/// Deserialize a response body, logging and falling back to the default if
+/// that fails. This is for the tracking endpoints, where callers have usually
+/// already bought a label and a status we cannot read should not stop them.
+async fn parse_json_or_default<T>(resp: reqwest::Response) -> T
+where
+    T: DeserializeOwned + Default,
+{
+    parse_json(resp).await.unwrap_or_else(|e| {
+        log::warn!("[shippo] {}", e);
+        T::default()
+    })
+}
+
+fn parse_json_body<T>(status_code: StatusCode, body: &str) -> Result<T, APIError>
+where
+    T: DeserializeOwned,
+{
+    let de = &mut serde_json::Deserializer::from_str(body);
+    serde_path_to_error::deserialize(de).map_err(|e| APIError {
+        status_code,
+        body: format!(
+            "deserializing response failed at `{}`: {}, body: {}",
+            e.path(),
+            e.inner(),
+            body
+        ),
+    })
+}
+
+/// Find the lowest priced rate, optionally only considering rates that
+/// arrive within `max_days`. Rates without a parseable amount are skipped, as
+/// are rates without an estimate when `max_days` is set.
+fn cheapest_of(rates: &[Rate], max_days: Option<i64>) -> Option<Rate> {
+    rates
+        .iter()
+        .filter(|r| match max_days {
+            Some(max) => r.estimated_days.map(|d| d <= max).unwrap_or(false),
+            None => true,
+        })
+        .filter_map(|r| r.amount.parse::<f64>().ok().map(|amount| (amount, r)))
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, r)| r.clone())
+}
+
+/// Get the query for the page from a `next` link. A link we cannot parse is
+/// returned as an `APIError` with the link in the body.
+fn next_page_query(next: &str) -> Result<Vec<(String, String)>, APIError> {
+    let url = Url::parse(next).map_err(|e| APIError {
+        status_code: StatusCode::BAD_GATEWAY,
+        body: format!("parsing next page link `{next}` failed: {e}"),
+    })?;
+    let pairs: Vec<(Cow<'_, str>, Cow<'_, str>)> = url.query_pairs().collect();
+    let mut new_pairs: Vec<(String, String)> = Vec::new();
+    for (a, b) in pairs {
+        let sa = a.into_owned();
+        let sb = b.into_owned();
+        new_pairs.push((sa, sb));
+    }
+
+    Ok(new_pairs)
+}
+
+/// The first delay between polls when waiting on a shipment or transaction.
+const POLL_INITIAL_DELAY: Duration = Duration::from_millis(500);
+/// The longest delay between polls when waiting on a shipment or transaction.
+const POLL_MAX_DELAY: Duration = Duration::from_secs(5);
+
+/// Shipments and transactions are processed asynchronously, these statuses
+/// mean Shippo is not done with them yet.
+fn is_pending_status(status: &str) -> bool {
+    status == "WAITING" || status == "QUEUED"
+}
+
+/// Sleep before the next poll and return the delay to use after that, or
+/// `None` if the timeout has elapsed.
+async fn poll_backoff(start: Instant, timeout: Duration, delay: Duration) -> Option<Duration> {
+    let elapsed = start.elapsed();
+    if elapsed >= timeout {
+        return None;
+    }
+
+    tokio::time::sleep(delay.min(timeout - elapsed)).await;
+
+    Some((delay * 2).min(POLL_MAX_DELAY))
+}
+
+/// The maximum date range, in days, the shipments list endpoint accepts.
+const MAX_SHIPMENTS_RANGE_DAYS: i64 = 90;
+
+/// Build the query for listing shipments created in a date range.
+fn shipments_created_between_query(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<(String, String)>, APIError> {
+    if end < start {
+        return Err(APIError {
+            status_code: StatusCode::BAD_REQUEST,
+            body: format!("invalid date range: end {end} is before start {start}"),
+        });
+    }
+
+    if end - start > chrono::Duration::days(MAX_SHIPMENTS_RANGE_DAYS) {
+        return Err(APIError {
+            status_code: StatusCode::BAD_REQUEST,
+            body: format!(
+                "invalid date range: {start} to {end} is longer than the {MAX_SHIPMENTS_RANGE_DAYS} days Shippo allows"
+            ),
+        });
+    }
+
+    Ok(vec![
+        (
+            "object_created_gt".to_string(),
+            start.to_rfc3339_opts(SecondsFormat::Secs, true),
+        ),
+        (
+            "object_created_lt".to_string(),
+            end.to_rfc3339_opts(SecondsFormat::Secs, true),
+        ),
+    ])
+}
+
 /// The data type for an API response.
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct APIResponse {
@@ -536,6 +996,26 @@ pub struct APIResponse {
     pub shipments: Vec<Shipment>,
 }
 
+/// The data type for a page of results from a list endpoint.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PaginatedResponse<T> {
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_string::deserialize",
+        skip_serializing_if = "String::is_empty"
+    )]
+    pub next: String,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_string::deserialize",
+        skip_serializing_if = "String::is_empty"
+    )]
+    pub previous: String,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub results: Vec<T>,
+}
+
 /// The data type for an API response.
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct OrdersAPIResponse {
@@ -649,6 +1129,10 @@ pub struct Shipment {
     /// will initially be empty.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub rates: Vec<Rate>,
+    /// An array containing messages returned by the carriers, for example
+    /// why rates could not be generated.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub messages: Vec<Message>,
     /// Indicates whether the object has been created in test mode.
     #[serde(default)]
     pub test: bool,
@@ -683,6 +1167,113 @@ pub struct CarrierAccount {
     pub test: bool,
 }
 
+impl CarrierAccount {
+    /// The carrier for the account, parsed from its token.
+    pub fn parsed_carrier(&self) -> Carrier {
+        Carrier::from(self.carrier.as_str())
+    }
+}
+
+/// A shipping carrier, as identified by its Shippo token.
+/// Tokens that do not have a variant are kept as `Other`.
+/// FROM: https://goshippo.com/docs/reference#carriers
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Carrier {
+    Usps,
+    Ups,
+    Fedex,
+    DhlExpress,
+    DhlEcommerce,
+    DhlGermany,
+    CanadaPost,
+    Purolator,
+    Ontrac,
+    Lasership,
+    AustraliaPost,
+    RoyalMail,
+    DeutschePost,
+    Other(String),
+}
+
+impl Carrier {
+    /// The Shippo token for the carrier.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Carrier::Usps => "usps",
+            Carrier::Ups => "ups",
+            Carrier::Fedex => "fedex",
+            Carrier::DhlExpress => "dhl_express",
+            Carrier::DhlEcommerce => "dhl_ecommerce",
+            Carrier::DhlGermany => "dhl_germany",
+            Carrier::CanadaPost => "canada_post",
+            Carrier::Purolator => "purolator",
+            Carrier::Ontrac => "ontrac",
+            Carrier::Lasership => "lasership",
+            Carrier::AustraliaPost => "australia_post",
+            Carrier::RoyalMail => "royal_mail",
+            Carrier::DeutschePost => "deutsche_post",
+            Carrier::Other(s) => s,
+        }
+    }
+}
+
+impl fmt::Display for Carrier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for Carrier {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "usps" => Carrier::Usps,
+            "ups" => Carrier::Ups,
+            "fedex" => Carrier::Fedex,
+            "dhl_express" => Carrier::DhlExpress,
+            "dhl_ecommerce" => Carrier::DhlEcommerce,
+            "dhl_germany" => Carrier::DhlGermany,
+            "canada_post" => Carrier::CanadaPost,
+            "purolator" => Carrier::Purolator,
+            "ontrac" => Carrier::Ontrac,
+            "lasership" => Carrier::Lasership,
+            "australia_post" => Carrier::AustraliaPost,
+            "royal_mail" => Carrier::RoyalMail,
+            "deutsche_post" => Carrier::DeutschePost,
+            _ => Carrier::Other(s.to_string()),
+        })
+    }
+}
+
+impl From<&str> for Carrier {
+    fn from(s: &str) -> Self {
+        match Carrier::from_str(s) {
+            Ok(c) => c,
+            Err(e) => match e {},
+        }
+    }
+}
+
+impl Serialize for Carrier {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Carrier {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Ok(Carrier::from(s.as_str()))
+    }
+}
+
 /// The data type for an address.
 /// FROM: https://goshippo.com/docs/reference#addresses
 #[derive(Clone, Debug, Default, JsonSchema, Serialize, Deserialize)]
@@ -777,6 +1368,52 @@ impl Address {
         .trim()
         .to_string()
     }
+
+    /// Format the address following the conventions of its country. Empty
+    /// parts are left out rather than leaving stray commas or spaces. US
+    /// addresses use the same format as `formatted`.
+    pub fn formatted_intl(&self) -> String {
+        if self.country.is_empty() || self.country == "US" {
+            return self.formatted();
+        }
+
+        let join = |parts: &[&str]| -> String {
+            parts
+                .iter()
+                .map(|p| p.trim())
+                .filter(|p| !p.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        let mut lines: Vec<String> = vec![self.street1.to_string(), self.street2.to_string()];
+        match self.country.as_str() {
+            // The postcode goes on its own line after the town.
+            "GB" | "IE" => {
+                lines.push(self.city.to_string());
+                lines.push(self.state.to_string());
+                lines.push(self.zip.to_string());
+            }
+            // The postcode goes before the town.
+            "AT" | "BE" | "CH" | "CZ" | "DE" | "DK" | "ES" | "FI" | "FR" | "IT" | "LU" | "NL" | "NO" | "PL" | "PT"
+            | "SE" => {
+                lines.push(join(&[&self.zip, &self.city]));
+                lines.push(self.state.to_string());
+            }
+            // Everywhere else the town, region, and postcode share a line.
+            _ => {
+                lines.push(join(&[&self.city, &self.state, &self.zip]));
+            }
+        }
+        lines.push(self.country.to_string());
+
+        lines
+            .iter()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
 }
 
 /// The data type for a parcel.
@@ -900,6 +1537,17 @@ pub struct Rate {
     /// Object ID of the carrier account that has been used to retrieve the rate.
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub carrier_account: String,
+    /// The insurance amount included in the rate price, expressed in the
+    /// currency used in the sender's country.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub included_insurance_price: Option<String>,
+    /// The parcel's transit zone token, used by some carriers to price the rate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zone: Option<String>,
+    /// Latest time the parcel is expected to be delivered on the estimated day,
+    /// e.g. "10:30:00", if the carrier provides it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arrives_by: Option<String>,
     /// Indicates whether the object has been created in test mode.
     #[serde(default)]
     pub test: bool,
@@ -949,6 +1597,47 @@ pub struct NewShipment {
     /// Customs Declarations object for an international shipment.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub customs_declaration: Option<CustomsDeclaration>,
+    /// Extra services and options for the shipment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra: Option<ShipmentExtra>,
+    /// Whether rates are generated asynchronously. When `false` the rates are
+    /// returned with the created shipment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub r#async: Option<bool>,
+}
+
+impl NewShipment {
+    /// Build a return shipment for an existing shipment. The sender and
+    /// recipient are swapped and the shipment is flagged as a return, so the
+    /// carrier produces a return label.
+    pub fn return_for(original: &Shipment) -> Self {
+        NewShipment {
+            address_from: original.address_to.clone(),
+            address_to: original.address_from.clone(),
+            parcels: original.parcels.clone(),
+            customs_declaration: original.customs_declaration.clone(),
+            extra: Some(ShipmentExtra {
+                is_return: true,
+                ..Default::default()
+            }),
+            r#async: None,
+        }
+    }
+}
+
+/// Extra services and options for a shipment.
+/// FROM: https://goshippo.com/docs/reference#shipment-extras
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ShipmentExtra {
+    /// Whether the label is for a return shipment.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub is_return: bool,
+    /// Optional text to be printed on the label.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub reference_1: String,
+    /// Optional text to be printed on the label.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub reference_2: String,
 }
 
 /// The data type for a pickup.
@@ -1154,6 +1843,45 @@ pub struct NewTransaction {
     pub label_file_type: String,
     #[serde(default)]
     pub r#async: bool,
+    /// Whether this is meant to be a test label. This is not sent to Shippo,
+    /// it is only used by `validate_against` to warn about purchasing a live
+    /// rate as a test label or the other way around.
+    #[serde(skip)]
+    pub test: Option<bool>,
+}
+
+/// Rates can only be purchased for this many days after they are created.
+const RATE_PURCHASE_WINDOW_DAYS: i64 = 7;
+
+impl NewTransaction {
+    /// Check that a label can still be purchased for `rate`, that is the rate
+    /// is within the window in which it can be purchased. If `test` is set and
+    /// does not match the rate's `test` flag a warning is logged, since mixing
+    /// test and live objects leads to confusing errors from Shippo.
+    pub fn validate_against(&self, rate: &Rate) -> Result<(), String> {
+        if let Some(test) = self.test {
+            if test != rate.test {
+                log::warn!(
+                    "[shippo] transaction has test set to {} but rate {} has test set to {}",
+                    test,
+                    rate.object_id,
+                    rate.test
+                );
+            }
+        }
+
+        let age = Utc::now() - rate.object_created;
+        if age > chrono::Duration::days(RATE_PURCHASE_WINDOW_DAYS) {
+            return Err(format!(
+                "rate {} was created {} days ago, only rates from the last {} days can be purchased",
+                rate.object_id,
+                age.num_days(),
+                RATE_PURCHASE_WINDOW_DAYS
+            ));
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Clone, Debug, Default, JsonSchema, Serialize, Deserialize)]
@@ -1177,6 +1905,48 @@ pub struct ValidationResults {
     pub messages: Vec<Message>,
 }
 
+/// The data type for a webhook.
+/// FROM: https://goshippo.com/docs/reference#webhooks
+#[derive(Clone, Debug, Default, JsonSchema, Serialize, Deserialize)]
+pub struct Webhook {
+    /// Unique identifier of the given Webhook object.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub object_id: String,
+    /// The URL Shippo will send notifications to.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub url: String,
+    /// The type of event that triggers the webhook.
+    /// "transaction_created" | "transaction_updated" | "track_updated" | "batch_created" | "batch_purchased" | "all"
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub event: String,
+    /// Whether the webhook is active.
+    #[serde(default)]
+    pub active: bool,
+    /// Whether the webhook only receives events for test mode objects.
+    #[serde(default)]
+    pub is_test: bool,
+    /// Date and time of Webhook creation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object_created: Option<DateTime<Utc>>,
+    /// Date and time of last Webhook update.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object_updated: Option<DateTime<Utc>>,
+    /// Username of the user who created the Webhook object.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub object_owner: String,
+}
+
+/// The data type for creating a webhook.
+#[derive(Clone, Debug, Default, JsonSchema, Serialize, Deserialize)]
+pub struct NewWebhook {
+    pub url: String,
+    pub event: String,
+    #[serde(default)]
+    pub active: bool,
+    #[serde(default)]
+    pub is_test: bool,
+}
+
 /// The data type for a tracking status.
 /// Tracking Status objects are used to track shipments.
 /// FROM: https://goshippo.com/docs/reference#tracks
@@ -1213,6 +1983,7 @@ pub struct TrackingStatus {
     /// The estimated time of arrival according to the carrier, this might be
     /// updated by carriers during the life of the shipment.
     #[serde(
+        default,
         deserialize_with = "null_date_format::deserialize",
         skip_serializing_if = "Option::is_none"
     )]
@@ -1220,6 +1991,7 @@ pub struct TrackingStatus {
     /// The estimated time of arrival according to the carrier at the time the
     /// shipment first entered the system.
     #[serde(
+        default,
         deserialize_with = "null_date_format::deserialize",
         skip_serializing_if = "Option::is_none"
     )]
@@ -1243,6 +2015,40 @@ pub struct TrackingStatus {
     pub metadata: String,
 }
 
+impl TrackingStatus {
+    /// Returns when the shipment was delivered, the date of the first
+    /// "DELIVERED" status, or `None` if it has not been delivered.
+    pub fn delivered_at(&self) -> Option<DateTime<Utc>> {
+        self.tracking_history
+            .iter()
+            .chain(self.tracking_status.iter())
+            .find(|s| s.status == "DELIVERED")
+            .and_then(|s| s.status_date)
+    }
+
+    /// Returns the tracking history as a list of dates and descriptions, oldest
+    /// first. Events without a date are skipped.
+    pub fn timeline(&self) -> Vec<(DateTime<Utc>, String)> {
+        let mut timeline: Vec<(DateTime<Utc>, String)> = self
+            .tracking_history
+            .iter()
+            .filter_map(|s| {
+                let details = if s.status_details.is_empty() {
+                    s.status.to_string()
+                } else {
+                    s.status_details.to_string()
+                };
+
+                s.status_date.map(|date| (date, details))
+            })
+            .collect();
+
+        timeline.sort_by_key(|(date, _)| *date);
+
+        timeline
+    }
+}
+
 #[derive(Clone, Default, Debug, JsonSchema, Serialize, Deserialize)]
 pub struct Status {
     /// Indicates the high level status of the shipment.
@@ -1445,6 +2251,8 @@ pub struct Order {
     )]
     pub weight_unit: String,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub line_items: Vec<LineItem>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub transactions: Vec<Transaction>,
     #[serde(
         default,
@@ -1498,6 +2306,128 @@ pub struct Order {
     pub test: bool,
 }
 
+/// The data type for creating an order.
+/// FROM: https://goshippo.com/docs/reference#orders-create
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NewOrder {
+    /// Address object of the recipient / buyer.
+    pub to_address: Address,
+    /// Address object of the sender / seller. Will be returned expanded by default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from_address: Option<Address>,
+    /// Array of line item objects representing the items in this order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub line_items: Vec<LineItem>,
+    /// Date and time when the order was placed.
+    pub placed_at: DateTime<Utc>,
+    /// An alphanumeric identifier for the order used by the seller/buyer.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub order_number: String,
+    /// Current state of the order.
+    /// "UNKNOWN" | "AWAITPAY" | "PAID" | "REFUNDED" | "CANCELLED" | "PARTIALLY_FULFILLED" | "SHIPPED"
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub order_status: String,
+    /// Amount paid by the buyer for shipping.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub shipping_cost: String,
+    /// Currency of the shipping_cost amount.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub shipping_cost_currency: String,
+    /// Shipping method (carrier + service or other free text description) chosen by the buyer.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub shipping_method: String,
+    /// Total price of the line items, before tax and shipping.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub subtotal_price: String,
+    /// Total amount paid by the buyer for this order.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub total_price: String,
+    /// Total tax amount paid by the buyer for this order.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub total_tax: String,
+    /// Currency of the total_price and total_tax amounts.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub currency: String,
+    /// Total weight of the order.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub weight: String,
+    /// The unit used for weight.
+    /// "g" | "oz" | "lb" | "kg"
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub weight_unit: String,
+    /// Custom buyer- or seller-provided notes about the order.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+}
+
+/// A line item in an order.
+/// FROM: https://goshippo.com/docs/reference#orders
+#[derive(Clone, Debug, Default, JsonSchema, Serialize, Deserialize)]
+pub struct LineItem {
+    /// Unique identifier of the given object.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub object_id: String,
+    /// Title of the product.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_string::deserialize",
+        skip_serializing_if = "String::is_empty"
+    )]
+    pub title: String,
+    /// Title of the product variant, e.g. the size or color.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_string::deserialize",
+        skip_serializing_if = "String::is_empty"
+    )]
+    pub variant_title: String,
+    /// Stock keeping unit of the product.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_string::deserialize",
+        skip_serializing_if = "String::is_empty"
+    )]
+    pub sku: String,
+    /// Quantity of this item in the order.
+    #[serde(default)]
+    pub quantity: i64,
+    /// Total price paid by the buyer for this item, i.e. quantity * price per item.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_string::deserialize",
+        skip_serializing_if = "String::is_empty"
+    )]
+    pub total_price: String,
+    /// Currency of the total_price amount.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_string::deserialize",
+        skip_serializing_if = "String::is_empty"
+    )]
+    pub currency: String,
+    /// Total weight of this item, i.e. quantity * weight per item.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_string::deserialize",
+        skip_serializing_if = "String::is_empty"
+    )]
+    pub weight: String,
+    /// The unit used for weight.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_string::deserialize",
+        skip_serializing_if = "String::is_empty"
+    )]
+    pub weight_unit: String,
+    /// Country of origin of the item. Example: 'US' or 'DE'.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_null_string::deserialize",
+        skip_serializing_if = "String::is_empty"
+    )]
+    pub manufacture_country: String,
+}
+
 /// A customs item object.
 /// Customs items are distinct items in your international shipment parcel.
 /// FROM: https://goshippo.com/docs/reference#customs-items
@@ -1595,7 +2525,9 @@ pub mod null_date_format {
             return Ok(None);
         }
 
-        Ok(Some(Utc.datetime_from_str(&s, "%+").unwrap()))
+        Utc.datetime_from_str(&s, "%+")
+            .map(Some)
+            .map_err(serde::de::Error::custom)
     }
 }
 
@@ -1655,3 +2587,562 @@ impl<'de> Visitor<'de> for BoolVisitor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_customs_declaration_deserialize() {
+        let body = r#"{
+            "object_created": "2014-07-17T00:04:06.163Z",
+            "object_updated": "2014-07-17T00:04:06.163Z",
+            "object_id": "e2a5d3ef1e5f4e8e8c4cd0f9d2a3bd14",
+            "object_owner": "shippotle@goshippo.com",
+            "object_state": "VALID",
+            "certify_signer": "Simon Kreuz",
+            "certify": true,
+            "items": ["0c1a723687164307bb2175972fbcd9ef"],
+            "non_delivery_option": "RETURN",
+            "contents_type": "MERCHANDISE",
+            "contents_explanation": "T-Shirt purchase",
+            "exporter_reference": "",
+            "importer_reference": "",
+            "invoice": "INV-123123",
+            "license": "",
+            "certificate": "",
+            "notes": "",
+            "eel_pfc": "NOEEI_30_37_a",
+            "aes_itn": "",
+            "disclaimer": "",
+            "incoterm": "DDP",
+            "metadata": "Order ID 123123",
+            "test": true
+        }"#;
+
+        let cd: CustomsDeclaration = serde_json::from_str(body).unwrap();
+
+        assert_eq!(cd.object_id, "e2a5d3ef1e5f4e8e8c4cd0f9d2a3bd14");
+        assert_eq!(cd.object_state, "VALID");
+        assert!(cd.certify);
+        assert_eq!(cd.items, vec!["0c1a723687164307bb2175972fbcd9ef"]);
+        assert_eq!(cd.contents_type, "MERCHANDISE");
+        assert_eq!(cd.eel_pfc, "NOEEI_30_37_a");
+        assert!(cd.test);
+    }
+
+    #[test]
+    fn test_new_order_round_trip() {
+        let order = NewOrder {
+            to_address: Address {
+                name: "Mr Hippo".to_string(),
+                street1: "965 Mission St".to_string(),
+                city: "San Francisco".to_string(),
+                state: "CA".to_string(),
+                zip: "94105".to_string(),
+                country: "US".to_string(),
+                ..Default::default()
+            },
+            from_address: None,
+            line_items: vec![LineItem {
+                title: "Hippo Shirt".to_string(),
+                sku: "HM-123".to_string(),
+                quantity: 2,
+                total_price: "24.00".to_string(),
+                currency: "USD".to_string(),
+                weight: "0.8".to_string(),
+                weight_unit: "lb".to_string(),
+                ..Default::default()
+            }],
+            placed_at: "2016-09-23T01:28:12Z".parse().unwrap(),
+            order_number: "1068".to_string(),
+            order_status: "PAID".to_string(),
+            shipping_cost: "12.83".to_string(),
+            shipping_cost_currency: "USD".to_string(),
+            shipping_method: "USPS First Class Package".to_string(),
+            subtotal_price: "24.00".to_string(),
+            total_price: "36.83".to_string(),
+            total_tax: "0.00".to_string(),
+            currency: "USD".to_string(),
+            weight: "0.8".to_string(),
+            weight_unit: "lb".to_string(),
+            notes: "".to_string(),
+        };
+
+        let body = serde_json::to_string(&order).unwrap();
+        let parsed: NewOrder = serde_json::from_str(&body).unwrap();
+
+        assert_eq!(parsed.to_address.street1, order.to_address.street1);
+        assert!(parsed.from_address.is_none());
+        assert_eq!(parsed.line_items.len(), 1);
+        assert_eq!(parsed.line_items[0].sku, "HM-123");
+        assert_eq!(parsed.line_items[0].quantity, 2);
+        assert_eq!(parsed.placed_at, order.placed_at);
+        assert_eq!(parsed.total_price, "36.83");
+        // Empty fields are not sent.
+        assert!(!body.contains("\"notes\""));
+    }
+
+    #[test]
+    fn test_parcel_deserialize() {
+        let body = r#"{
+            "object_state": "VALID",
+            "object_created": "2014-07-09T02:19:13.174Z",
+            "object_updated": "2014-07-09T02:19:13.174Z",
+            "object_id": "7df2ecf8b4224763ab7c71fae7ec8274",
+            "object_owner": "shippotle@goshippo.com",
+            "template": null,
+            "length": "5",
+            "width": "5",
+            "height": "5",
+            "distance_unit": "in",
+            "weight": "2",
+            "mass_unit": "lb",
+            "metadata": "Customer ID 123456",
+            "test": true
+        }"#;
+
+        let parcel: Parcel = serde_json::from_str(body).unwrap();
+
+        assert_eq!(parcel.object_state, "VALID");
+        assert_eq!(parcel.object_id, "7df2ecf8b4224763ab7c71fae7ec8274");
+        assert_eq!(parcel.distance_unit, "in");
+        assert_eq!(parcel.weight, "2");
+        assert!(parcel.object_created.is_some());
+    }
+
+    #[test]
+    fn test_customs_items_list_deserialize() {
+        let body = r#"{
+            "next": "https://api.goshippo.com/customs/items/?page=2",
+            "previous": null,
+            "results": [
+                {
+                    "object_created": "2014-07-16T23:20:31.089Z",
+                    "object_updated": "2014-07-16T23:20:31.089Z",
+                    "object_id": "55358464c7b740aca07259d2e0d9f3e6",
+                    "object_owner": "shippotle@goshippo.com",
+                    "object_state": "VALID",
+                    "description": "T-Shirt",
+                    "quantity": 2,
+                    "net_weight": "400",
+                    "mass_unit": "g",
+                    "value_amount": "20",
+                    "value_currency": "USD",
+                    "tariff_number": "",
+                    "sku_code": null,
+                    "origin_country": "US",
+                    "metadata": "Order ID '123123'",
+                    "test": true
+                }
+            ]
+        }"#;
+
+        let r: PaginatedResponse<CustomsItem> = serde_json::from_str(body).unwrap();
+
+        assert_eq!(r.next, "https://api.goshippo.com/customs/items/?page=2");
+        assert!(r.previous.is_empty());
+        assert_eq!(r.results.len(), 1);
+        assert_eq!(r.results[0].description, "T-Shirt");
+        assert_eq!(r.results[0].quantity, 2);
+        assert!(r.results[0].sku_code.is_empty());
+    }
+
+    #[test]
+    fn test_carrier_round_trip() {
+        for (token, carrier) in [
+            ("usps", Carrier::Usps),
+            ("ups", Carrier::Ups),
+            ("fedex", Carrier::Fedex),
+            ("dhl_express", Carrier::DhlExpress),
+            ("dhl_ecommerce", Carrier::DhlEcommerce),
+            ("canada_post", Carrier::CanadaPost),
+        ] {
+            assert_eq!(token.parse::<Carrier>().unwrap(), carrier);
+            assert_eq!(carrier.as_str(), token);
+
+            let json = serde_json::to_string(&carrier).unwrap();
+            assert_eq!(json, format!("\"{token}\""));
+            assert_eq!(serde_json::from_str::<Carrier>(&json).unwrap(), carrier);
+        }
+
+        // Tokens are matched regardless of case.
+        assert_eq!(Carrier::from("USPS"), Carrier::Usps);
+
+        // Unknown tokens are preserved.
+        let other = Carrier::from("asendia_us");
+        assert_eq!(other, Carrier::Other("asendia_us".to_string()));
+        assert_eq!(other.as_str(), "asendia_us");
+    }
+
+    #[test]
+    fn test_rate_extra_fields_deserialize() {
+        let body = r#"{
+            "object_created": "2013-12-09T01:56:52.780Z",
+            "object_id": "adcfdddf8ec64b84ad22772bce3ea37a",
+            "object_owner": "shippotle@goshippo.com",
+            "shipment": "5e40ead7cffe4cc1ad45108696162e42",
+            "attributes": ["CHEAPEST"],
+            "amount": "5.50",
+            "currency": "USD",
+            "amount_local": "5.50",
+            "currency_local": "USD",
+            "provider": "USPS",
+            "servicelevel": {
+                "name": "Priority Mail",
+                "token": "usps_priority",
+                "terms": ""
+            },
+            "estimated_days": 2,
+            "arrives_by": "10:30:00",
+            "duration_terms": "Delivery in 1 to 3 business days.",
+            "carrier_account": "078870331023437cb917f5187429b093",
+            "included_insurance_price": "100.00",
+            "zone": "3",
+            "test": false
+        }"#;
+
+        let rate: Rate = serde_json::from_str(body).unwrap();
+
+        assert_eq!(rate.included_insurance_price.as_deref(), Some("100.00"));
+        assert_eq!(rate.zone.as_deref(), Some("3"));
+        assert_eq!(rate.arrives_by.as_deref(), Some("10:30:00"));
+
+        // The fields are optional and may be null.
+        let body = r#"{
+            "object_created": "2013-12-09T01:56:52.780Z",
+            "object_id": "adcfdddf8ec64b84ad22772bce3ea37a",
+            "amount": "5.50",
+            "arrives_by": null,
+            "zone": null
+        }"#;
+
+        let rate: Rate = serde_json::from_str(body).unwrap();
+
+        assert!(rate.included_insurance_price.is_none());
+        assert!(rate.zone.is_none());
+        assert!(rate.arrives_by.is_none());
+    }
+
+    #[test]
+    fn test_shipments_created_between_query() {
+        let start = DateTime::parse_from_rfc3339("2021-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let end = DateTime::parse_from_rfc3339("2021-03-01T12:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let query = shipments_created_between_query(start, end).unwrap();
+        assert_eq!(
+            query,
+            vec![
+                ("object_created_gt".to_string(), "2021-01-01T00:00:00Z".to_string()),
+                ("object_created_lt".to_string(), "2021-03-01T12:30:00Z".to_string()),
+            ]
+        );
+
+        // Exactly 90 days is allowed, anything longer is not.
+        assert!(shipments_created_between_query(start, start + chrono::Duration::days(90)).is_ok());
+        let err = shipments_created_between_query(start, start + chrono::Duration::days(91)).unwrap_err();
+        assert_eq!(err.status_code, StatusCode::BAD_REQUEST);
+        assert!(err.body.contains("90 days"));
+
+        // The end must not be before the start.
+        assert!(shipments_created_between_query(end, start).is_err());
+    }
+
+    #[test]
+    fn test_new_shipment_return_for() {
+        let original: Shipment = serde_json::from_value(serde_json::json!({
+            "status": "SUCCESS",
+            "object_created": "2021-01-01T00:00:00Z",
+            "object_updated": "2021-01-01T00:00:00Z",
+            "object_id": "5e40ead7cffe4cc1ad45108696162e42",
+            "address_from": {"name": "Oxide Computer Company", "city": "Emeryville", "country": "US"},
+            "address_to": {"name": "Jane Doe", "city": "Austin", "country": "US"},
+            "parcels": [{"length": "12", "width": "12", "height": "6", "distance_unit": "in", "weight": "2", "mass_unit": "lb"}],
+            "shipment_date": "2021-01-02T00:00:00Z"
+        }))
+        .unwrap();
+
+        let ns = NewShipment::return_for(&original);
+        assert_eq!(ns.address_from.name, "Jane Doe");
+        assert_eq!(ns.address_to.name, "Oxide Computer Company");
+        assert_eq!(ns.parcels.len(), 1);
+
+        let body = serde_json::to_value(&ns).unwrap();
+        assert_eq!(body["extra"], serde_json::json!({"is_return": true}));
+        assert_eq!(body["address_from"]["city"], "Austin");
+        assert_eq!(body["address_to"]["city"], "Emeryville");
+    }
+
+    #[test]
+    fn test_webhook_deserialize() {
+        let w: PaginatedResponse<Webhook> = serde_json::from_str(
+            r#"{
+                "next": null,
+                "previous": null,
+                "results": [{
+                    "object_id": "e5c7b6c6c2b04b9aa2a10e4ac4eb56cf",
+                    "object_created": "2021-06-01T17:54:06.135Z",
+                    "object_updated": "2021-06-01T17:54:06.135Z",
+                    "object_owner": "shippo@example.com",
+                    "url": "https://webhooks.example.com/shippo/tracking",
+                    "event": "track_updated",
+                    "active": true,
+                    "is_test": false
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(w.results.len(), 1);
+        let w = &w.results[0];
+        assert_eq!(w.url, "https://webhooks.example.com/shippo/tracking");
+        assert_eq!(w.event, "track_updated");
+        assert!(w.active);
+        assert!(!w.is_test);
+        assert!(w.object_created.is_some());
+    }
+
+    #[test]
+    fn test_new_shipment_multipiece_serialize() {
+        let parcel = |weight: &str| Parcel {
+            length: "12".to_string(),
+            width: "12".to_string(),
+            height: "6".to_string(),
+            distance_unit: "in".to_string(),
+            weight: weight.to_string(),
+            mass_unit: "lb".to_string(),
+            ..Default::default()
+        };
+
+        let ns = NewShipment {
+            address_from: Address {
+                name: "Oxide Computer Company".to_string(),
+                ..Default::default()
+            },
+            address_to: Address {
+                name: "Jane Doe".to_string(),
+                ..Default::default()
+            },
+            parcels: vec![parcel("2"), parcel("5"), parcel("10")],
+            ..Default::default()
+        };
+
+        let body = serde_json::to_value(&ns).unwrap();
+        let parcels = body["parcels"].as_array().unwrap();
+        assert_eq!(parcels.len(), 3);
+        assert_eq!(parcels[0]["weight"], "2");
+        assert_eq!(parcels[1]["weight"], "5");
+        assert_eq!(parcels[2]["weight"], "10");
+    }
+
+    #[test]
+    fn test_test_mode_blocks_live_purchases() {
+        // Without test mode nothing is blocked.
+        assert!(Shippo::new("shippo_live_abc123").check_purchase_allowed().is_ok());
+
+        // A live token in test mode cannot purchase.
+        let err = Shippo::new("shippo_live_abc123")
+            .test_mode(true)
+            .check_purchase_allowed()
+            .unwrap_err();
+        assert_eq!(err.status_code, StatusCode::FORBIDDEN);
+
+        // A test token in test mode can.
+        assert!(Shippo::new("shippo_test_abc123")
+            .test_mode(true)
+            .check_purchase_allowed()
+            .is_ok());
+    }
+
+    fn tracking_status(history: serde_json::Value) -> TrackingStatus {
+        serde_json::from_value(serde_json::json!({
+            "carrier": "usps",
+            "tracking_number": "9205590164917312751089",
+            "eta": null,
+            "original_eta": null,
+            "tracking_status": null,
+            "tracking_history": history
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_tracking_status_delivered() {
+        // Deliberately out of order to check the timeline is sorted.
+        let ts = tracking_status(serde_json::json!([
+            {"status": "TRANSIT", "status_details": "Your shipment is in transit.", "status_date": "2021-07-02T09:00:00Z"},
+            {"status": "PRE_TRANSIT", "status_details": "", "status_date": "2021-07-01T12:00:00Z"},
+            {"status": "DELIVERED", "status_details": "Your shipment has been delivered.", "status_date": "2021-07-03T16:30:00Z"}
+        ]));
+
+        assert_eq!(
+            ts.delivered_at(),
+            Some(
+                DateTime::parse_from_rfc3339("2021-07-03T16:30:00Z")
+                    .unwrap()
+                    .with_timezone(&Utc)
+            )
+        );
+
+        let timeline: Vec<String> = ts.timeline().into_iter().map(|(_, details)| details).collect();
+        assert_eq!(
+            timeline,
+            vec![
+                "PRE_TRANSIT",
+                "Your shipment is in transit.",
+                "Your shipment has been delivered."
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tracking_status_in_transit() {
+        let ts = tracking_status(serde_json::json!([
+            {"status": "PRE_TRANSIT", "status_details": "Label created.", "status_date": "2021-07-01T12:00:00Z"},
+            {"status": "TRANSIT", "status_details": "Your shipment is in transit.", "status_date": "2021-07-02T09:00:00Z"}
+        ]));
+
+        assert_eq!(ts.delivered_at(), None);
+        assert_eq!(ts.timeline().len(), 2);
+        assert_eq!(ts.timeline()[1].1, "Your shipment is in transit.");
+    }
+
+    #[test]
+    fn test_parse_json_body_names_field() {
+        let body = r#"{"object_id": "abc123", "placed_at": "2021-06-01T12:00:00Z", "line_items": [{"title": "Hoodie", "quantity": "two"}]}"#;
+
+        let err = parse_json_body::<Order>(StatusCode::OK, body).unwrap_err();
+        assert_eq!(err.status_code, StatusCode::OK);
+        assert!(err.body.contains("`line_items[0].quantity`"), "{}", err.body);
+        assert!(err.body.contains(body));
+
+        let order: Order = parse_json_body(
+            StatusCode::OK,
+            r#"{"object_id": "abc123", "placed_at": "2021-06-01T12:00:00Z"}"#,
+        )
+        .unwrap();
+        assert_eq!(order.object_id, "abc123");
+    }
+
+    #[test]
+    fn test_address_formatted_intl() {
+        let us = Address {
+            street1: "1 Main St".to_string(),
+            city: "Emeryville".to_string(),
+            state: "CA".to_string(),
+            zip: "946081234".to_string(),
+            country: "US".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(us.formatted_intl(), us.formatted());
+        assert_eq!(us.formatted_intl(), "1 Main St\nEmeryville, CA 94608-1234 US");
+
+        let gb = Address {
+            street1: "10 Downing Street".to_string(),
+            city: "London".to_string(),
+            zip: "SW1A 2AA".to_string(),
+            country: "GB".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(gb.formatted_intl(), "10 Downing Street\nLondon\nSW1A 2AA\nGB");
+
+        let de = Address {
+            street1: "Unter den Linden 77".to_string(),
+            city: "Berlin".to_string(),
+            zip: "10117".to_string(),
+            country: "DE".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(de.formatted_intl(), "Unter den Linden 77\n10117 Berlin\nDE");
+    }
+
+    #[test]
+    fn test_cheapest_of() {
+        let rate = |id: &str, amount: &str, days: Option<i64>| -> Rate {
+            serde_json::from_value(serde_json::json!({
+                "object_created": "2021-01-01T00:00:00Z",
+                "object_id": id,
+                "amount": amount,
+                "currency": "USD",
+                "estimated_days": days
+            }))
+            .unwrap()
+        };
+
+        let rates = vec![
+            rate("express", "24.50", Some(1)),
+            rate("priority", "8.95", Some(2)),
+            rate("ground", "5.10", Some(5)),
+            rate("unknown", "4.00", None),
+        ];
+
+        assert_eq!(cheapest_of(&rates, None).unwrap().object_id, "unknown");
+        // The slower but cheaper rates are excluded.
+        assert_eq!(cheapest_of(&rates, Some(3)).unwrap().object_id, "priority");
+        assert_eq!(cheapest_of(&rates, Some(1)).unwrap().object_id, "express");
+        assert!(cheapest_of(&rates, Some(0)).is_none());
+        assert!(cheapest_of(&[], None).is_none());
+    }
+
+    #[test]
+    fn test_new_transaction_validate_against() {
+        let rate = |created: DateTime<Utc>| -> Rate {
+            serde_json::from_value(serde_json::json!({
+                "object_created": created,
+                "object_id": "rate123",
+                "amount": "5.10",
+                "currency": "USD"
+            }))
+            .unwrap()
+        };
+        let nt = NewTransaction {
+            rate: "rate123".to_string(),
+            ..Default::default()
+        };
+
+        assert!(nt
+            .validate_against(&rate(Utc::now() - chrono::Duration::days(2)))
+            .is_ok());
+
+        let err = nt
+            .validate_against(&rate(Utc::now() - chrono::Duration::days(8)))
+            .unwrap_err();
+        assert!(err.contains("8 days ago"), "{}", err);
+
+        // A mismatched test flag is only a warning.
+        let test = NewTransaction {
+            rate: "rate123".to_string(),
+            test: Some(true),
+            ..Default::default()
+        };
+        assert!(test.validate_against(&rate(Utc::now())).is_ok());
+    }
+
+    #[test]
+    fn test_parse_json_body_bad_date() {
+        let body = r#"{"carrier": "usps", "tracking_number": "9205590164917312751089", "eta": "next tuesday"}"#;
+
+        let err = parse_json_body::<TrackingStatus>(StatusCode::OK, body).unwrap_err();
+        assert!(err.body.contains("eta"), "{}", err.body);
+        assert!(err.body.contains("next tuesday"), "{}", err.body);
+    }
+
+    #[test]
+    fn test_next_page_query() {
+        let query = next_page_query("https://api.goshippo.com/orders/?page=2&results=25").unwrap();
+        assert_eq!(
+            query,
+            vec![
+                ("page".to_string(), "2".to_string()),
+                ("results".to_string(), "25".to_string())
+            ]
+        );
+
+        let err = next_page_query("/orders/?page=2").unwrap_err();
+        assert!(err.body.contains("/orders/?page=2"), "{}", err.body);
+    }
+}
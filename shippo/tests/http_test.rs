@@ -0,0 +1,333 @@
+use std::time::Duration;
+
+use chrono::{TimeZone, Utc};
+use futures::StreamExt;
+use httpmock::MockServer;
+use reqwest::Url;
+use serde_json::json;
+
+use shippo::{Address, Carrier, NewTransaction, Order, Parcel, Shippo};
+
+fn shipment_body(status: &str) -> serde_json::Value {
+    json!({
+        "status": status,
+        "object_created": "2021-01-01T00:00:00Z",
+        "object_updated": "2021-01-01T00:00:00Z",
+        "object_id": "5e40ead7cffe4cc1ad45108696162e42",
+        "shipment_date": "2021-01-02T00:00:00Z",
+        "rates": []
+    })
+}
+
+#[tokio::test]
+async fn wait_for_shipment_test() {
+    let server = MockServer::start();
+    let queued = server
+        .mock_async(|when, then| {
+            when.method("GET").path("/shipments/5e40ead7cffe4cc1ad45108696162e42");
+            then.status(200).json_body(shipment_body("QUEUED"));
+        })
+        .await;
+
+    let shippo = Shippo::new("token").base_url(Url::parse(&server.base_url()).unwrap());
+
+    // Once the shipment has been polled while queued, swap in the finished shipment.
+    let swap = async {
+        while queued.hits_async().await == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        queued.delete_async().await;
+
+        server
+            .mock_async(|when, then| {
+                when.method("GET").path("/shipments/5e40ead7cffe4cc1ad45108696162e42");
+                then.status(200).json_body(shipment_body("SUCCESS"));
+            })
+            .await
+    };
+
+    let (shipment, success) = tokio::join!(
+        shippo.wait_for_shipment("5e40ead7cffe4cc1ad45108696162e42", Duration::from_secs(10)),
+        swap
+    );
+
+    let shipment = shipment.unwrap();
+    assert_eq!(shipment.status, "SUCCESS");
+    success.assert_async().await;
+}
+
+#[tokio::test]
+async fn wait_for_transaction_timeout_test() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method("GET").path("/transactions/abc123");
+        then.status(200)
+            .json_body(json!({"object_id": "abc123", "status": "WAITING"}));
+    });
+
+    let shippo = Shippo::new("token").base_url(Url::parse(&server.base_url()).unwrap());
+
+    let err = shippo
+        .wait_for_transaction("abc123", Duration::from_millis(100))
+        .await
+        .unwrap_err();
+
+    assert_eq!(err.status_code, reqwest::StatusCode::REQUEST_TIMEOUT);
+    assert!(err.body.contains("WAITING"));
+    assert!(mock.hits() >= 1);
+}
+
+#[tokio::test]
+async fn test_mode_blocks_live_purchase_test() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method("POST").path("/transactions");
+        then.status(201)
+            .json_body(json!({"object_id": "abc123", "status": "SUCCESS"}));
+    });
+
+    let shippo = Shippo::new("shippo_live_abc123")
+        .base_url(Url::parse(&server.base_url()).unwrap())
+        .test_mode(true);
+
+    let err = shippo
+        .create_shipping_label_from_rate(NewTransaction {
+            rate: "rate123".to_string(),
+            r#async: false,
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+
+    assert_eq!(err.status_code, reqwest::StatusCode::FORBIDDEN);
+    mock.assert_hits(0);
+}
+
+fn order_body(id: &str) -> serde_json::Value {
+    json!({
+        "object_id": id,
+        "order_number": format!("#{id}"),
+        "placed_at": "2021-06-01T12:00:00Z",
+        "order_status": "PAID"
+    })
+}
+
+#[tokio::test]
+async fn stream_orders_test() {
+    let server = MockServer::start();
+    let mut first = server.mock(|when, then| {
+        when.method("GET").path("/orders");
+        then.status(200).json_body(json!({
+            "next": server.url("/orders?page=2&results=2"),
+            "previous": null,
+            "results": [order_body("1"), order_body("2")]
+        }));
+    });
+
+    let shippo = Shippo::new("token").base_url(Url::parse(&server.base_url()).unwrap());
+    let mut orders = Box::pin(shippo.stream_orders());
+
+    // Only the first page is fetched until it has been consumed.
+    assert_eq!(orders.next().await.unwrap().unwrap().object_id, "1");
+    assert_eq!(orders.next().await.unwrap().unwrap().object_id, "2");
+    first.assert();
+    first.delete();
+
+    let second = server.mock(|when, then| {
+        when.method("GET")
+            .path("/orders")
+            .query_param("page", "2")
+            .query_param("results", "2");
+        then.status(200).json_body(json!({
+            "next": null,
+            "previous": server.url("/orders?page=1&results=2"),
+            "results": [order_body("3")]
+        }));
+    });
+
+    let rest: Vec<Order> = orders.map(|o| o.unwrap()).collect().await;
+    assert_eq!(rest.len(), 1);
+    assert_eq!(rest[0].object_id, "3");
+    second.assert();
+}
+
+fn address(name: &str) -> Address {
+    Address {
+        name: name.to_string(),
+        street1: "1 Main St".to_string(),
+        city: "Emeryville".to_string(),
+        state: "CA".to_string(),
+        zip: "94608".to_string(),
+        country: "US".to_string(),
+        ..Default::default()
+    }
+}
+
+fn parcel() -> Parcel {
+    Parcel {
+        length: "12".to_string(),
+        width: "12".to_string(),
+        height: "6".to_string(),
+        distance_unit: "in".to_string(),
+        weight: "2".to_string(),
+        mass_unit: "lb".to_string(),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn shop_rates_test() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method("POST")
+            .path("/shipments")
+            .json_body_partial(json!({"async": false}).to_string());
+        then.status(201).json_body(json!({
+            "status": "SUCCESS",
+            "object_created": "2021-01-01T00:00:00Z",
+            "object_updated": "2021-01-01T00:00:00Z",
+            "object_id": "5e40ead7cffe4cc1ad45108696162e42",
+            "shipment_date": "2021-01-02T00:00:00Z",
+            "rates": [
+                {"object_created": "2021-01-01T00:00:00Z", "object_id": "rate1", "amount": "8.95", "currency": "USD"},
+                {"object_created": "2021-01-01T00:00:00Z", "object_id": "rate2", "amount": "5.10", "currency": "USD"}
+            ]
+        }));
+    });
+
+    let shippo = Shippo::new("token").base_url(Url::parse(&server.base_url()).unwrap());
+    let rates = shippo
+        .shop_rates(address("Oxide"), address("Jane Doe"), parcel())
+        .await
+        .unwrap();
+
+    let ids: Vec<&str> = rates.iter().map(|r| r.object_id.as_str()).collect();
+    assert_eq!(ids, vec!["rate1", "rate2"]);
+    mock.assert();
+}
+
+#[tokio::test]
+async fn shop_rates_error_test() {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method("POST").path("/shipments");
+        then.status(201).json_body(json!({
+            "status": "ERROR",
+            "object_created": "2021-01-01T00:00:00Z",
+            "object_updated": "2021-01-01T00:00:00Z",
+            "object_id": "5e40ead7cffe4cc1ad45108696162e42",
+            "shipment_date": "2021-01-02T00:00:00Z",
+            "rates": [],
+            "messages": [{"source": "USPS", "code": "", "text": "The destination address is invalid."}]
+        }));
+    });
+
+    let shippo = Shippo::new("token").base_url(Url::parse(&server.base_url()).unwrap());
+    let err = shippo
+        .shop_rates(address("Oxide"), address("Jane Doe"), parcel())
+        .await
+        .unwrap_err();
+
+    assert!(
+        err.body.contains("USPS: The destination address is invalid."),
+        "{}",
+        err.body
+    );
+}
+
+#[tokio::test]
+async fn api_error_body_test() {
+    // Network failure: nothing is listening on this port.
+    let shippo = Shippo::new("token").base_url(Url::parse("http://127.0.0.1:1/").unwrap());
+    let err = shippo.get_parcel("p1").await.unwrap_err();
+    assert!(err.body.starts_with("sending request failed"), "{}", err.body);
+
+    let server = MockServer::start();
+    let shippo = Shippo::new("token").base_url(Url::parse(&server.base_url()).unwrap());
+
+    // Unexpected status.
+    server.mock(|when, then| {
+        when.method("GET").path("/parcels/p1");
+        then.status(500).body("upstream exploded");
+    });
+    let err = shippo.get_parcel("p1").await.unwrap_err();
+    assert_eq!(err.status_code, reqwest::StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(err.body, "upstream exploded");
+
+    // Successful status with a body that is not what we expect.
+    server.mock(|when, then| {
+        when.method("GET").path("/parcels/p2");
+        then.status(200).body("<html>not json</html>");
+    });
+    let err = shippo.get_parcel("p2").await.unwrap_err();
+    assert_eq!(err.status_code, reqwest::StatusCode::OK);
+    assert!(err.body.contains("<html>not json</html>"), "{}", err.body);
+}
+
+#[tokio::test]
+async fn register_tracking_webhook_bad_body_test() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method("POST").path("/tracks");
+        then.status(201).body("<html>accepted</html>");
+    });
+
+    let shippo = Shippo::new("token").base_url(Url::parse(&server.base_url()).unwrap());
+
+    // The webhook is registered, so a body we cannot read is not an error.
+    let status = shippo
+        .register_tracking_webhook(&Carrier::Usps, "9205590164917312751089")
+        .await
+        .unwrap();
+    assert!(status.tracking_number.is_empty());
+
+    mock.assert();
+}
+
+#[tokio::test]
+async fn delete_webhook_empty_body_test() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method("DELETE").path("/webhooks/wh123");
+        then.status(204);
+    });
+
+    let shippo = Shippo::new("token").base_url(Url::parse(&server.base_url()).unwrap());
+    shippo.delete_webhook("wh123").await.unwrap();
+
+    mock.assert();
+}
+
+#[tokio::test]
+async fn list_shipments_between_test() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method("GET")
+            .path("/shipments")
+            .query_param("object_created_gt", "2021-01-01T00:00:00Z")
+            .query_param("object_created_lt", "2021-02-01T00:00:00Z");
+        then.status(200).json_body(json!({
+            "next": null,
+            "previous": null,
+            "results": [shipment_body("SUCCESS")]
+        }));
+    });
+
+    let shippo = Shippo::new("token").base_url(Url::parse(&server.base_url()).unwrap());
+
+    let start = Utc.with_ymd_and_hms(2021, 1, 1, 0, 0, 0).unwrap();
+    let shipments = shippo
+        .list_shipments_between(start, Utc.with_ymd_and_hms(2021, 2, 1, 0, 0, 0).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(shipments.len(), 1);
+    mock.assert();
+
+    // Ranges over 90 days are rejected before sending anything.
+    let err = shippo
+        .list_shipments_between(start, Utc.with_ymd_and_hms(2021, 6, 1, 0, 0, 0).unwrap())
+        .await
+        .unwrap_err();
+    assert_eq!(err.status_code, reqwest::StatusCode::BAD_REQUEST);
+    mock.assert_hits(1);
+}
@@ -25,10 +25,12 @@
  */
 #![allow(clippy::field_reassign_with_default)]
 #![allow(clippy::nonstandard_macro_braces)]
-use std::{collections::HashMap, env, sync::Arc};
+use std::{collections::HashMap, env, error, fmt, sync::Arc, time::Duration};
 
 use anyhow::{bail, Result};
-use reqwest::{header, Body, Client, Method, Request, StatusCode, Url};
+use chrono::{offset::Utc, DateTime};
+use futures::stream::{self, StreamExt};
+use reqwest::{header, Body, Client, Method, Request, Response, StatusCode, Url};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -36,6 +38,32 @@ use serde_json::Value;
 /// Endpoint for the Slack API.
 const ENDPOINT: &str = "https://slack.com/api/";
 
+/// The most times a request is attempted when it is rate limited or fails
+/// with a server error.
+const MAX_ATTEMPTS: u32 = 5;
+/// The first delay before retrying when Slack does not tell us how long to
+/// wait.
+const RETRY_INITIAL_DELAY: Duration = Duration::from_secs(1);
+
+/// The kind of token an API method needs.
+///
+/// Most methods use the bot token. The user token is used for the methods the
+/// bot token is not allowed to call:
+///
+/// - `admin.*`, e.g. inviting and removing users
+/// - `users.identity`
+/// - `team.billableInfo`
+/// - `users.profile.set`, to update other users' profiles
+/// - `reminders.*`
+///
+/// Calling one of those with the wrong kind of token fails with
+/// `not_allowed_token_type`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Bot,
+    User,
+}
+
 /// Entrypoint for interacting with the Slack API.
 pub struct Slack {
     token: String,
@@ -46,6 +74,7 @@ pub struct Slack {
     client_secret: String,
     redirect_uri: String,
     workspace_id: String,
+    base_url: Url,
 
     client: Arc<Client>,
 }
@@ -80,6 +109,7 @@ impl Slack {
                     redirect_uri: redirect_uri.to_string(),
                     token: token.to_string(),
                     user_token: user_token.to_string(),
+                    base_url: Url::parse(ENDPOINT).unwrap(),
 
                     client: Arc::new(c),
                 };
@@ -115,9 +145,23 @@ impl Slack {
         Slack::new(client_id, client_secret, workspace_id, redirect_uri, token, user_token)
     }
 
+    /// Set the base url for `Slack`
+    pub fn base_url(mut self, base_url: Url) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    /// Get the token to use for a kind of request.
+    fn token(&self, kind: TokenKind) -> &str {
+        match kind {
+            TokenKind::Bot => &self.token,
+            TokenKind::User => &self.user_token,
+        }
+    }
+
     fn request<B>(
         &self,
-        token: &str,
+        kind: TokenKind,
         method: Method,
         path: &str,
         body: B,
@@ -126,10 +170,9 @@ impl Slack {
     where
         B: Serialize,
     {
-        let base = Url::parse(ENDPOINT)?;
-        let url = base.join(path)?;
+        let url = self.base_url.join(path)?;
 
-        let bt = format!("Bearer {token}");
+        let bt = format!("Bearer {}", self.token(kind));
         let bearer = header::HeaderValue::from_str(&bt)?;
 
         // Set the default headers.
@@ -158,6 +201,45 @@ impl Slack {
         Ok(rb.build()?)
     }
 
+    /// Execute a request, retrying when Slack rate limits us or has a server
+    /// error. Rate limited requests wait for as long as the `Retry-After`
+    /// header says, anything else backs off exponentially.
+    ///
+    /// `build` is called to build the request for every attempt. This can be
+    /// used to call API methods this crate does not wrap yet, while still
+    /// respecting the rate limits. The last response is returned as is once
+    /// we run out of attempts, so callers should still check the status.
+    /// FROM: https://api.slack.com/docs/rate-limits
+    pub async fn execute_with_retry<F>(&self, build: F) -> Result<Response>
+    where
+        F: Fn() -> Result<Request>,
+    {
+        let mut delay = RETRY_INITIAL_DELAY;
+        let mut attempt = 1;
+
+        loop {
+            let resp = self.client.execute(build()?).await?;
+
+            let status = resp.status();
+            if attempt >= MAX_ATTEMPTS || !(status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()) {
+                return Ok(resp);
+            }
+
+            let wait = resp
+                .headers()
+                .get(header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(delay);
+
+            tokio::time::sleep(wait).await;
+
+            delay *= 2;
+            attempt += 1;
+        }
+    }
+
     pub fn user_consent_url(&self) -> String {
         let state = uuid::Uuid::new_v4();
         format!(
@@ -206,7 +288,7 @@ impl Slack {
         // Build the request.
         // TODO: paginate.
         let request = self.request(
-            &self.token,
+            TokenKind::Bot,
             Method::GET,
             "users.list",
             (),
@@ -230,7 +312,7 @@ impl Slack {
     /// FROM: https://api.slack.com/methods/users.identity
     pub async fn current_user(&self) -> Result<CurrentUser> {
         // Build the request.
-        let request = self.request(&self.user_token, Method::GET, "users.identity", (), None)?;
+        let request = self.request(TokenKind::User, Method::GET, "users.identity", (), None)?;
 
         let resp = self.client.execute(request).await?;
         match resp.status() {
@@ -249,7 +331,7 @@ impl Slack {
     pub async fn billable_info(&self) -> Result<HashMap<String, BillableInfo>> {
         // Build the request.
         // TODO: paginate.
-        let request = self.request(&self.user_token, Method::GET, "team.billableInfo", (), None)?;
+        let request = self.request(TokenKind::User, Method::GET, "team.billableInfo", (), None)?;
 
         let resp = self.client.execute(request).await?;
         match resp.status() {
@@ -264,10 +346,41 @@ impl Slack {
     }
 
     /// Open a view.
+    /// If the trigger id has expired this returns a `SlackError::TriggerExpired`.
     /// FROM: https://api.slack.com/methods/views.open
     pub async fn open_view(&self, body: &View) -> Result<Modal> {
         // Build the request.
-        let request = self.request(&self.token, Method::POST, "views.open", body, None)?;
+        let request = self.request(TokenKind::Bot, Method::POST, "views.open", body, None)?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let r: ViewResponse = resp.json().await?;
+
+        r.into_view()
+    }
+
+    /// Open a view from a raw JSON view payload, for views that cannot be
+    /// expressed with the `Modal` type.
+    /// If the trigger id has expired this returns a `SlackError::TriggerExpired`.
+    /// FROM: https://api.slack.com/methods/views.open
+    pub async fn open_view_json(&self, trigger_id: &str, view: Value) -> Result<()> {
+        // Build the request.
+        let request = self.request(
+            TokenKind::Bot,
+            Method::POST,
+            "views.open",
+            OpenViewRequest {
+                trigger_id: trigger_id.to_string(),
+                view,
+            },
+            None,
+        )?;
 
         let resp = self.client.execute(request).await?;
         match resp.status() {
@@ -279,6 +392,26 @@ impl Slack {
 
         let r: ViewResponse = resp.json().await?;
 
+        r.into_view()?;
+        Ok(())
+    }
+
+    /// List the custom emoji for the workspace, as a map of emoji name to
+    /// either an image URL or `alias:<name>` for aliases of another emoji.
+    /// FROM: https://api.slack.com/methods/emoji.list
+    pub async fn list_emoji(&self) -> Result<HashMap<String, String>> {
+        let request = self.request(TokenKind::Bot, Method::GET, "emoji.list", (), None)?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let r: EmojiListResponse = resp.json().await?;
+
         if !r.ok {
             bail!(
                 "status code: {}, body: {}",
@@ -286,7 +419,8 @@ impl Slack {
                 serde_json::json!(r).to_string()
             );
         }
-        Ok(r.view)
+
+        Ok(r.emoji)
     }
 
     /// Get channel id from name.
@@ -304,11 +438,49 @@ impl Slack {
         );
     }
 
+    /// Resolve a channel to its id. Names are expected to start with `#`,
+    /// anything else is assumed to already be a channel id.
+    async fn resolve_channel_id(&self, channel: &str) -> Result<String> {
+        if channel.starts_with('#') {
+            // We have a name not a channel id.
+            return self.channel_id(channel).await;
+        }
+
+        Ok(channel.to_string())
+    }
+
+    /// Get the information for a single channel.
+    /// If the channel does not exist this returns a `SlackError::ChannelNotFound`.
+    /// FROM: https://api.slack.com/methods/conversations.info
+    pub async fn get_conversation_info(&self, channel: &str) -> Result<Channel> {
+        let channel_id = self.resolve_channel_id(channel).await?;
+
+        let request = self.request(
+            TokenKind::Bot,
+            Method::GET,
+            "conversations.info",
+            (),
+            Some(vec![("channel", channel_id.to_string())]),
+        )?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let r: ConversationInfoResponse = resp.json().await?;
+
+        r.into_channel(&channel_id)
+    }
+
     /// List channels, defaults to public channels.
     /// FROM: https://api.slack.com/methods/conversations.list
     pub async fn list_channels(&self) -> Result<Vec<Channel>> {
         // Build the request.
-        let mut request = self.request(&self.token, Method::GET, "conversations.list", (), None)?;
+        let mut request = self.request(TokenKind::Bot, Method::GET, "conversations.list", (), None)?;
 
         let mut resp = self.client.execute(request).await?;
         match resp.status() {
@@ -333,7 +505,7 @@ impl Slack {
         // Paginate.
         while !r.response_metadata.next_cursor.is_empty() {
             request = self.request(
-                &self.token,
+                TokenKind::Bot,
                 Method::GET,
                 "conversations.list",
                 (),
@@ -365,13 +537,19 @@ impl Slack {
         Ok(channels)
     }
 
-    /// Invite a user to a workspace.
-    /// FROM: https://api.slack.com/methods/admin.users.invite
-    pub async fn invite_user(&self, invite: UserInvite) -> Result<()> {
+    /// List the user ids of the members of a channel.
+    /// FROM: https://api.slack.com/methods/conversations.members
+    pub async fn get_conversation_members(&self, channel: &str) -> Result<Vec<String>> {
         // Build the request.
-        let request = self.request(&self.user_token, Method::POST, "admin.users.invite", invite, None)?;
+        let mut request = self.request(
+            TokenKind::Bot,
+            Method::GET,
+            "conversations.members",
+            (),
+            Some(vec![("channel", channel.to_string())]),
+        )?;
 
-        let resp = self.client.execute(request).await?;
+        let mut resp = self.client.execute(request).await?;
         match resp.status() {
             StatusCode::OK => (),
             s => {
@@ -379,24 +557,61 @@ impl Slack {
             }
         };
 
-        Ok(())
-    }
+        let mut r: ConversationMembersResponse = resp.json().await?;
 
-    /// Join a channel.
-    /// FROM: https://api.slack.com/methods/conversations.join
-    pub async fn join_channel(&self, channel: &str) -> Result<Channel> {
-        let mut channel_id = channel.to_string();
-        if channel.starts_with('#') {
-            // We have a name not a channel id.
-            // First get the channel id.
-            // We must have a channel id to join the channel.
-            channel_id = self.channel_id(channel).await?;
+        if !r.ok {
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(r).to_string()
+            );
         }
 
-        let mut body: HashMap<&str, &str> = HashMap::new();
-        body.insert("channel", &channel_id);
+        let mut members = r.members;
+
+        // Paginate.
+        while !r.response_metadata.next_cursor.is_empty() {
+            request = self.request(
+                TokenKind::Bot,
+                Method::GET,
+                "conversations.members",
+                (),
+                Some(vec![
+                    ("channel", channel.to_string()),
+                    ("cursor", r.response_metadata.next_cursor.to_string()),
+                ]),
+            )?;
+
+            resp = self.client.execute(request).await?;
+            match resp.status() {
+                StatusCode::OK => (),
+                s => {
+                    bail!("status code: {}, body: {}", s, resp.text().await?);
+                }
+            };
+
+            // Try to deserialize the response.
+            r = resp.json().await?;
+
+            if !r.ok {
+                bail!(
+                    "status code: {}, body: {}",
+                    StatusCode::OK,
+                    serde_json::json!(r).to_string()
+                );
+            }
 
-        let request = self.request(&self.token, Method::POST, "conversations.join", body, None)?;
+            members.append(&mut r.members);
+        }
+
+        Ok(members)
+    }
+
+    /// Get information about the workspace.
+    /// FROM: https://api.slack.com/methods/team.info
+    pub async fn get_team_info(&self) -> Result<TeamInfo> {
+        // Build the request.
+        let request = self.request(TokenKind::Bot, Method::GET, "team.info", (), None)?;
 
         let resp = self.client.execute(request).await?;
         match resp.status() {
@@ -406,43 +621,65 @@ impl Slack {
             }
         };
 
-        let f: JoinChannelResponse = resp.json().await?;
+        let r: TeamInfoResponse = resp.json().await?;
 
-        if !f.ok {
+        if !r.ok {
             bail!(
                 "status code: {}, body: {}",
                 StatusCode::OK,
-                serde_json::json!(f).to_string()
+                serde_json::json!(r).to_string()
             );
         }
 
-        Ok(f.channel)
+        Ok(r.team)
     }
 
-    /// Post message to a channel.
-    /// If the authenticated token is not a member of the channel, it will attempt be added.
-    /// FROM: https://api.slack.com/methods/chat.postMessage
-    pub async fn post_message(&self, body: &FormattedMessage) -> Result<FormattedMessageResponse> {
-        let f = self.post_message_raw(body).await?;
+    /// List the user groups for the workspace, including their members.
+    /// FROM: https://api.slack.com/methods/usergroups.list
+    pub async fn list_usergroups(&self) -> Result<Vec<UserGroup>> {
+        let request = self.request(
+            TokenKind::Bot,
+            Method::GET,
+            "usergroups.list",
+            (),
+            Some(vec![("include_users", "true".to_string())]),
+        )?;
 
-        if !f.ok {
-            if f.error.contains("not_in_channel") {
-                // Join the channel and try again.
-                self.join_channel(&body.channel).await?;
-                return self.post_message_raw(body).await;
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
             }
+        };
+
+        let r: ListUserGroupsResponse = resp.json().await?;
+
+        if !r.ok {
             bail!(
                 "status code: {}, body: {}",
                 StatusCode::OK,
-                serde_json::json!(f).to_string()
+                serde_json::json!(r).to_string()
             );
         }
 
-        Ok(f)
+        Ok(r.usergroups)
     }
 
-    pub async fn post_message_raw(&self, body: &FormattedMessage) -> Result<FormattedMessageResponse> {
-        let request = self.request(&self.token, Method::POST, "chat.postMessage", body, None)?;
+    /// Create a user group. The handle is what is used to mention the group,
+    /// e.g. `@marketing`.
+    /// FROM: https://api.slack.com/methods/usergroups.create
+    pub async fn create_usergroup(&self, handle: &str, name: &str) -> Result<UserGroup> {
+        let request = self.request(
+            TokenKind::Bot,
+            Method::POST,
+            "usergroups.create",
+            CreateUserGroupRequest {
+                handle: handle.to_string(),
+                name: name.to_string(),
+            },
+            None,
+        )?;
 
         let resp = self.client.execute(request).await?;
         match resp.status() {
@@ -452,19 +689,32 @@ impl Slack {
             }
         };
 
-        let f: FormattedMessageResponse = resp.json().await?;
+        let r: UserGroupResponse = resp.json().await?;
 
-        Ok(f)
+        if !r.ok {
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(r).to_string()
+            );
+        }
+
+        Ok(r.usergroup)
     }
 
-    /// Remove users from a workspace.
-    /// FROM: https://api.slack.com/methods/admin.users.remove
-    pub async fn remove_user(&self, user_id: &str) -> Result<()> {
-        // Build the request.
-        let mut body: HashMap<&str, &str> = HashMap::new();
-        body.insert("team_id", &self.workspace_id);
-        body.insert("user_id", user_id);
-        let request = self.request(&self.user_token, Method::POST, "admin.users.remove", body, None)?;
+    /// Replace the members of a user group with the given users.
+    /// FROM: https://api.slack.com/methods/usergroups.users.update
+    pub async fn update_usergroup_users(&self, usergroup_id: &str, user_ids: &[&str]) -> Result<UserGroup> {
+        let request = self.request(
+            TokenKind::Bot,
+            Method::POST,
+            "usergroups.users.update",
+            UpdateUserGroupUsersRequest {
+                usergroup: usergroup_id.to_string(),
+                users: user_ids.join(","),
+            },
+            None,
+        )?;
 
         let resp = self.client.execute(request).await?;
         match resp.status() {
@@ -474,25 +724,62 @@ impl Slack {
             }
         };
 
+        let r: UserGroupResponse = resp.json().await?;
+
+        if !r.ok {
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(r).to_string()
+            );
+        }
+
+        Ok(r.usergroup)
+    }
+
+    /// Invite a user to a workspace.
+    /// FROM: https://api.slack.com/methods/admin.users.invite
+    pub async fn invite_user(&self, invite: UserInvite) -> Result<()> {
+        self.send_invite(&invite).await?;
+
         Ok(())
     }
 
-    /// Set a user's profile information, including custom status.
-    /// FROM: https://api.slack.com/methods/users.profile.set
-    pub async fn update_user_profile(&self, user_id: &str, profile: UserProfile) -> Result<()> {
-        // Build the request.
-        let request = self.request(
-            &self.user_token,
-            Method::POST,
-            "users.profile.set",
-            UpdateUserProfileRequest {
-                user: user_id.to_string(),
-                profile,
-            },
-            None,
-        )?;
+    /// Invite a batch of users, with at most `concurrency` invites in flight at
+    /// once. Returns the result for each email, in the same order as `invites`.
+    /// Unlike `invite_user`, an invite Slack answers with `ok: false` (for
+    /// example "already_in_team") is returned as an error for that email.
+    /// FROM: https://api.slack.com/methods/admin.users.invite
+    pub async fn admin_invite_users(&self, invites: &[UserInvite], concurrency: usize) -> Vec<(String, Result<()>)> {
+        stream::iter(invites)
+            .map(|invite| async move { (invite.email.to_string(), self.invite_user_checked(invite).await) })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
 
-        let resp = self.client.execute(request).await?;
+    /// Send an invite and check that Slack accepted it.
+    async fn invite_user_checked(&self, invite: &UserInvite) -> Result<()> {
+        let r: BasicResponse = self.send_invite(invite).await?.json().await?;
+
+        if !r.ok {
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(r).to_string()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Send an invite, retrying if we are rate limited, and return the
+    /// response if the status is OK.
+    async fn send_invite(&self, invite: &UserInvite) -> Result<Response> {
+        // Build the request.
+        let resp = self
+            .execute_with_retry(|| self.request(TokenKind::User, Method::POST, "admin.users.invite", invite, None))
+            .await?;
         match resp.status() {
             StatusCode::OK => (),
             s => {
@@ -500,14 +787,21 @@ impl Slack {
             }
         };
 
-        Ok(())
+        Ok(resp)
     }
 
-    /// Post text to a channel.
-    pub async fn post_to_channel(url: &str, v: &Value) -> Result<()> {
-        let client = Client::new();
-        let resp = client.post(url).body(Body::from(v.to_string())).send().await?;
+    /// Join a channel.
+    /// FROM: https://api.slack.com/methods/conversations.join
+    pub async fn join_channel(&self, channel: &str) -> Result<Channel> {
+        // We must have a channel id to join the channel.
+        let channel_id = self.resolve_channel_id(channel).await?;
+
+        let mut body: HashMap<&str, &str> = HashMap::new();
+        body.insert("channel", &channel_id);
+
+        let request = self.request(TokenKind::Bot, Method::POST, "conversations.join", body, None)?;
 
+        let resp = self.client.execute(request).await?;
         match resp.status() {
             StatusCode::OK => (),
             s => {
@@ -515,11 +809,931 @@ impl Slack {
             }
         };
 
-        Ok(())
-    }
-}
+        let f: JoinChannelResponse = resp.json().await?;
 
-/// A message to be sent in Slack.
+        if !f.ok {
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(f).to_string()
+            );
+        }
+
+        Ok(f.channel)
+    }
+
+    /// Set the topic of a channel.
+    /// FROM: https://api.slack.com/methods/conversations.setTopic
+    pub async fn set_channel_topic(&self, channel: &str, topic: &str) -> Result<()> {
+        let channel_id = self.resolve_channel_id(channel).await?;
+
+        let request = self.request(
+            TokenKind::Bot,
+            Method::POST,
+            "conversations.setTopic",
+            SetChannelTopicRequest {
+                channel: channel_id,
+                topic: topic.to_string(),
+            },
+            None,
+        )?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let r: BasicResponse = resp.json().await?;
+
+        if !r.ok {
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(r).to_string()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Set the purpose of a channel.
+    /// FROM: https://api.slack.com/methods/conversations.setPurpose
+    pub async fn set_channel_purpose(&self, channel: &str, purpose: &str) -> Result<()> {
+        let channel_id = self.resolve_channel_id(channel).await?;
+
+        let request = self.request(
+            TokenKind::Bot,
+            Method::POST,
+            "conversations.setPurpose",
+            SetChannelPurposeRequest {
+                channel: channel_id,
+                purpose: purpose.to_string(),
+            },
+            None,
+        )?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let r: BasicResponse = resp.json().await?;
+
+        if !r.ok {
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(r).to_string()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Make sure a channel has the given topic, purpose and bookmarks, so
+    /// provisioning a channel can be run over and over. The topic and purpose
+    /// are only set if they differ from the current ones, and bookmarks, given
+    /// as `(title, link)`, are only added if there is no bookmark for that link
+    /// yet. Existing bookmarks are never removed.
+    pub async fn ensure_channel_metadata(
+        &self,
+        channel: &str,
+        topic: Option<&str>,
+        purpose: Option<&str>,
+        bookmarks: &[(String, String)],
+    ) -> Result<()> {
+        let channel_id = self.resolve_channel_id(channel).await?;
+        let info = self.get_conversation_info(&channel_id).await?;
+
+        if let Some(topic) = topic {
+            if info.topic.value != topic {
+                self.set_channel_topic(&channel_id, topic).await?;
+            }
+        }
+
+        if let Some(purpose) = purpose {
+            if info.purpose.value != purpose {
+                self.set_channel_purpose(&channel_id, purpose).await?;
+            }
+        }
+
+        if bookmarks.is_empty() {
+            return Ok(());
+        }
+
+        let existing = self.list_bookmarks(&channel_id).await?;
+        for (title, link) in bookmarks {
+            if !existing.iter().any(|b| &b.link == link) {
+                self.add_bookmark(&channel_id, title, link, None).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Archive a channel. Archiving a channel that is already archived is not
+    /// treated as an error.
+    /// FROM: https://api.slack.com/methods/conversations.archive
+    pub async fn archive_channel(&self, channel: &str) -> Result<()> {
+        let channel_id = self.resolve_channel_id(channel).await?;
+
+        let request = self.request(
+            TokenKind::Bot,
+            Method::POST,
+            "conversations.archive",
+            ChannelRequest { channel: channel_id },
+            None,
+        )?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let r: BasicResponse = resp.json().await?;
+
+        if !r.ok && r.error != "already_archived" {
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(r).to_string()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Remove a user from a channel. A user who is not in the channel is not
+    /// treated as an error.
+    /// FROM: https://api.slack.com/methods/conversations.kick
+    pub async fn kick_from_channel(&self, channel: &str, user: &str) -> Result<()> {
+        let channel_id = self.resolve_channel_id(channel).await?;
+
+        let request = self.request(
+            TokenKind::Bot,
+            Method::POST,
+            "conversations.kick",
+            KickFromChannelRequest {
+                channel: channel_id,
+                user: user.to_string(),
+            },
+            None,
+        )?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let r: BasicResponse = resp.json().await?;
+
+        if !r.ok && r.error != "not_in_channel" {
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(r).to_string()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Leave a channel. Leaving a channel we are not in is not treated as an
+    /// error.
+    /// FROM: https://api.slack.com/methods/conversations.leave
+    pub async fn leave_channel(&self, channel: &str) -> Result<()> {
+        let channel_id = self.resolve_channel_id(channel).await?;
+
+        let request = self.request(
+            TokenKind::Bot,
+            Method::POST,
+            "conversations.leave",
+            ChannelRequest { channel: channel_id },
+            None,
+        )?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let r: BasicResponse = resp.json().await?;
+
+        if !r.ok && r.error != "not_in_channel" {
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(r).to_string()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Open a direct message with one or more users, returning the channel id
+    /// to use with `post_message`. Passing more than one user opens a
+    /// multi-person direct message. If the conversation is already open, the
+    /// existing channel id is returned.
+    /// FROM: https://api.slack.com/methods/conversations.open
+    pub async fn open_dm(&self, user_ids: &[&str]) -> Result<String> {
+        if user_ids.is_empty() {
+            bail!("at least one user id is required to open a direct message");
+        }
+
+        let request = self.request(
+            TokenKind::Bot,
+            Method::POST,
+            "conversations.open",
+            OpenConversationRequest {
+                users: user_ids.join(","),
+            },
+            None,
+        )?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let r: OpenConversationResponse = resp.json().await?;
+
+        if !r.ok {
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(r).to_string()
+            );
+        }
+
+        Ok(r.channel.id)
+    }
+
+    /// Post message to a channel.
+    /// If the authenticated token is not a member of the channel, it will attempt be added.
+    /// FROM: https://api.slack.com/methods/chat.postMessage
+    pub async fn post_message(&self, body: &FormattedMessage) -> Result<FormattedMessageResponse> {
+        let f = self.post_message_raw(body).await?;
+
+        if !f.ok {
+            if f.error.contains("not_in_channel") {
+                // Join the channel and try again.
+                self.join_channel(&body.channel).await?;
+                return self.post_message_raw(body).await;
+            }
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(f).to_string()
+            );
+        }
+
+        Ok(f)
+    }
+
+    pub async fn post_message_raw(&self, body: &FormattedMessage) -> Result<FormattedMessageResponse> {
+        let request = self.request(TokenKind::Bot, Method::POST, "chat.postMessage", body, None)?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let f: FormattedMessageResponse = resp.json().await?;
+
+        Ok(f)
+    }
+
+    /// Post an ephemeral message to a channel, visible only to the given user.
+    /// The user must be a member of the channel.
+    /// FROM: https://api.slack.com/methods/chat.postEphemeral
+    pub async fn post_ephemeral(
+        &self,
+        channel: &str,
+        user: &str,
+        text: &str,
+        blocks: Option<Vec<MessageBlock>>,
+    ) -> Result<()> {
+        let channel_id = self.resolve_channel_id(channel).await?;
+
+        let request = self.request(
+            TokenKind::Bot,
+            Method::POST,
+            "chat.postEphemeral",
+            PostEphemeralRequest {
+                channel: channel_id,
+                user: user.to_string(),
+                text: text.to_string(),
+                blocks: blocks.unwrap_or_default(),
+            },
+            None,
+        )?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let r: BasicResponse = resp.json().await?;
+
+        if !r.ok {
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(r).to_string()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Get a permalink for a message, for example one returned from `post_message`.
+    /// FROM: https://api.slack.com/methods/chat.getPermalink
+    pub async fn get_permalink(&self, channel: &str, message_ts: &str) -> Result<String> {
+        let channel_id = self.resolve_channel_id(channel).await?;
+
+        let request = self.request(
+            TokenKind::Bot,
+            Method::GET,
+            "chat.getPermalink",
+            (),
+            Some(vec![("channel", channel_id), ("message_ts", message_ts.to_string())]),
+        )?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let r: PermalinkResponse = resp.json().await?;
+
+        if !r.ok {
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(r).to_string()
+            );
+        }
+
+        Ok(r.permalink)
+    }
+
+    /// Pin a message to a channel. Pinning a message that is already pinned is
+    /// not treated as an error.
+    /// FROM: https://api.slack.com/methods/pins.add
+    pub async fn pin_message(&self, channel: &str, ts: &str) -> Result<()> {
+        let channel_id = self.resolve_channel_id(channel).await?;
+
+        let request = self.request(
+            TokenKind::Bot,
+            Method::POST,
+            "pins.add",
+            PinRequest {
+                channel: channel_id,
+                timestamp: ts.to_string(),
+            },
+            None,
+        )?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let r: BasicResponse = resp.json().await?;
+
+        if !r.ok && r.error != "already_pinned" {
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(r).to_string()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Unpin a message from a channel. Unpinning a message that is not pinned
+    /// is not treated as an error.
+    /// FROM: https://api.slack.com/methods/pins.remove
+    pub async fn unpin_message(&self, channel: &str, ts: &str) -> Result<()> {
+        let channel_id = self.resolve_channel_id(channel).await?;
+
+        let request = self.request(
+            TokenKind::Bot,
+            Method::POST,
+            "pins.remove",
+            PinRequest {
+                channel: channel_id,
+                timestamp: ts.to_string(),
+            },
+            None,
+        )?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let r: BasicResponse = resp.json().await?;
+
+        if !r.ok && r.error != "no_pin" {
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(r).to_string()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// List the items pinned to a channel.
+    /// FROM: https://api.slack.com/methods/pins.list
+    pub async fn list_pins(&self, channel: &str) -> Result<Vec<PinnedItem>> {
+        let channel_id = self.resolve_channel_id(channel).await?;
+
+        let request = self.request(
+            TokenKind::Bot,
+            Method::GET,
+            "pins.list",
+            (),
+            Some(vec![("channel", channel_id)]),
+        )?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let r: ListPinsResponse = resp.json().await?;
+
+        if !r.ok {
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(r).to_string()
+            );
+        }
+
+        Ok(r.items)
+    }
+
+    /// Add a link bookmark to a channel.
+    /// FROM: https://api.slack.com/methods/bookmarks.add
+    pub async fn add_bookmark(&self, channel: &str, title: &str, link: &str, emoji: Option<&str>) -> Result<Bookmark> {
+        let channel_id = self.resolve_channel_id(channel).await?;
+
+        let request = self.request(
+            TokenKind::Bot,
+            Method::POST,
+            "bookmarks.add",
+            AddBookmarkRequest {
+                channel_id,
+                title: title.to_string(),
+                type_: "link".to_string(),
+                link: link.to_string(),
+                emoji: emoji.unwrap_or_default().to_string(),
+            },
+            None,
+        )?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let r: BookmarkResponse = resp.json().await?;
+
+        if !r.ok {
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(r).to_string()
+            );
+        }
+
+        Ok(r.bookmark)
+    }
+
+    /// List the bookmarks in a channel.
+    /// FROM: https://api.slack.com/methods/bookmarks.list
+    pub async fn list_bookmarks(&self, channel: &str) -> Result<Vec<Bookmark>> {
+        let channel_id = self.resolve_channel_id(channel).await?;
+
+        let request = self.request(
+            TokenKind::Bot,
+            Method::GET,
+            "bookmarks.list",
+            (),
+            Some(vec![("channel_id", channel_id)]),
+        )?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let r: ListBookmarksResponse = resp.json().await?;
+
+        if !r.ok {
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(r).to_string()
+            );
+        }
+
+        Ok(r.bookmarks)
+    }
+
+    /// Add a reminder. If no user is given the reminder is for the user who
+    /// owns the token. Reminders do not support bot tokens, so this uses the
+    /// user token.
+    /// FROM: https://api.slack.com/methods/reminders.add
+    pub async fn add_reminder(&self, text: &str, time: ReminderTime, user: Option<&str>) -> Result<Reminder> {
+        let request = self.request(
+            TokenKind::User,
+            Method::POST,
+            "reminders.add",
+            AddReminderRequest {
+                text: text.to_string(),
+                time,
+                user: user.unwrap_or_default().to_string(),
+            },
+            None,
+        )?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let r: ReminderResponse = resp.json().await?;
+
+        if !r.ok {
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(r).to_string()
+            );
+        }
+
+        Ok(r.reminder)
+    }
+
+    /// List the reminders created by or for the user who owns the token.
+    /// FROM: https://api.slack.com/methods/reminders.list
+    pub async fn list_reminders(&self) -> Result<Vec<Reminder>> {
+        let request = self.request(TokenKind::User, Method::GET, "reminders.list", (), None)?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let r: ListRemindersResponse = resp.json().await?;
+
+        if !r.ok {
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(r).to_string()
+            );
+        }
+
+        Ok(r.reminders)
+    }
+
+    /// List files, optionally only those in a channel or uploaded by a user.
+    /// FROM: https://api.slack.com/methods/files.list
+    pub async fn list_files(&self, channel: Option<&str>, user: Option<&str>) -> Result<Vec<SlackFile>> {
+        let mut query = Vec::new();
+        if let Some(channel) = channel {
+            query.push(("channel", self.resolve_channel_id(channel).await?));
+        }
+        if let Some(user) = user {
+            query.push(("user", user.to_string()));
+        }
+
+        let mut files = Vec::new();
+        let mut page = 1;
+
+        // This endpoint uses page numbers rather than cursors.
+        loop {
+            let mut q = query.clone();
+            q.push(("page", page.to_string()));
+
+            let request = self.request(TokenKind::Bot, Method::GET, "files.list", (), Some(q))?;
+
+            let resp = self.client.execute(request).await?;
+            match resp.status() {
+                StatusCode::OK => (),
+                s => {
+                    bail!("status code: {}, body: {}", s, resp.text().await?);
+                }
+            };
+
+            let mut r: ListFilesResponse = resp.json().await?;
+
+            if !r.ok {
+                bail!(
+                    "status code: {}, body: {}",
+                    StatusCode::OK,
+                    serde_json::json!(r).to_string()
+                );
+            }
+
+            files.append(&mut r.files);
+
+            if r.paging.page >= r.paging.pages {
+                break;
+            }
+            page = r.paging.page + 1;
+        }
+
+        Ok(files)
+    }
+
+    /// Delete a file.
+    /// FROM: https://api.slack.com/methods/files.delete
+    pub async fn delete_file(&self, file_id: &str) -> Result<()> {
+        let mut body: HashMap<&str, &str> = HashMap::new();
+        body.insert("file", file_id);
+
+        let request = self.request(TokenKind::Bot, Method::POST, "files.delete", body, None)?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let r: BasicResponse = resp.json().await?;
+
+        if !r.ok {
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(r).to_string()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Remove users from a workspace.
+    /// FROM: https://api.slack.com/methods/admin.users.remove
+    pub async fn remove_user(&self, user_id: &str) -> Result<()> {
+        // Build the request.
+        let mut body: HashMap<&str, &str> = HashMap::new();
+        body.insert("team_id", &self.workspace_id);
+        body.insert("user_id", user_id);
+        let request = self.request(TokenKind::User, Method::POST, "admin.users.remove", body, None)?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Set a user's profile information, including custom status.
+    /// FROM: https://api.slack.com/methods/users.profile.set
+    pub async fn update_user_profile(&self, user_id: &str, profile: UserProfile) -> Result<()> {
+        // Build the request.
+        let request = self.request(
+            TokenKind::User,
+            Method::POST,
+            "users.profile.set",
+            UpdateUserProfileRequest {
+                user: user_id.to_string(),
+                profile,
+            },
+            None,
+        )?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Get a user's current Do Not Disturb status.
+    /// FROM: https://api.slack.com/methods/dnd.info
+    pub async fn get_dnd_info(&self, user: &str) -> Result<DndInfo> {
+        // Build the request.
+        let request = self.request(
+            TokenKind::Bot,
+            Method::GET,
+            "dnd.info",
+            (),
+            Some(vec![("user", user.to_string())]),
+        )?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let r: DndInfo = resp.json().await?;
+
+        if !r.ok {
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(r).to_string()
+            );
+        }
+
+        Ok(r)
+    }
+
+    /// Get a user's presence information.
+    /// FROM: https://api.slack.com/methods/users.getPresence
+    pub async fn get_user_presence(&self, user: &str) -> Result<UserPresence> {
+        // Build the request.
+        let request = self.request(
+            TokenKind::Bot,
+            Method::GET,
+            "users.getPresence",
+            (),
+            Some(vec![("user", user.to_string())]),
+        )?;
+
+        let resp = self.client.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let r: UserPresence = resp.json().await?;
+
+        if !r.ok {
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(r).to_string()
+            );
+        }
+
+        Ok(r)
+    }
+
+    /// Respond to a slash command.
+    /// This posts to the `response_url` Slack sent with the command, not to the
+    /// API, so no token is attached.
+    /// FROM: https://api.slack.com/interactivity/handling#message_responses
+    pub async fn respond_to_command(&self, response_url: &str, resp: SlashCommandResponse) -> Result<()> {
+        let resp = self.client.post(response_url).json(&resp).send().await?;
+
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Post text to a channel.
+    pub async fn post_to_channel(url: &str, v: &Value) -> Result<()> {
+        let client = Client::new();
+        let resp = client.post(url).body(Body::from(v.to_string())).send().await?;
+
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        Ok(())
+    }
+}
+
+/// Escape text for use in a Slack message.
+///
+/// Slack treats `&`, `<` and `>` as control characters, so any untrusted text
+/// (user input, issue titles, etc.) should be passed through this before being
+/// interpolated into a message. Otherwise it can break formatting or inject
+/// mentions like `<!channel>` or `<@U123>`.
+///
+/// Docs: https://api.slack.com/reference/surfaces/formatting#escaping
+pub fn escape_mrkdwn(text: &str) -> String {
+    // The ampersand must be replaced first so we do not double escape.
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Format a date so Slack renders it in each reader's own timezone and locale.
+///
+/// `token_string` is the format to display, using Slack's tokens such as
+/// `{date_short}` or `{time}`, e.g. `"{date_short_pretty} at {time}"`. If a
+/// `link` is given the date will link to it. `fallback` is shown by clients
+/// that cannot render the date.
+///
+/// Docs: https://api.slack.com/reference/surfaces/formatting#date-formatting
+pub fn format_date(ts: DateTime<Utc>, token_string: &str, link: Option<&str>, fallback: &str) -> String {
+    let mut date = format!("<!date^{}^{}", ts.timestamp(), token_string);
+    if let Some(link) = link {
+        date.push('^');
+        date.push_str(link);
+    }
+    date.push('|');
+    date.push_str(&escape_mrkdwn(fallback));
+    date.push('>');
+
+    date
+}
+
+/// Errors from the Slack API that callers may want to handle on their own.
+/// These are returned inside the `anyhow::Error` and can be matched with
+/// `err.downcast_ref::<SlackError>()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlackError {
+    /// The trigger id used to open a view has expired. Trigger ids are only
+    /// valid for three seconds after the interaction that created them.
+    TriggerExpired,
+    /// The channel does not exist, or is not visible to the token.
+    ChannelNotFound(String),
+}
+
+impl fmt::Display for SlackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SlackError::TriggerExpired => write!(f, "the trigger id has expired"),
+            SlackError::ChannelNotFound(channel) => write!(f, "channel not found: {channel}"),
+        }
+    }
+}
+
+impl error::Error for SlackError {}
+
+/// A message to be sent in Slack.
 ///
 /// Docs: https://api.slack.com/interactivity/slash-commands#responding_to_commands
 #[derive(Debug, Deserialize, JsonSchema, Serialize)]
@@ -542,6 +1756,53 @@ pub enum MessageResponseType {
     InChannel,
 }
 
+/// A response to a slash command, posted to the command's `response_url`.
+///
+/// Docs: https://api.slack.com/interactivity/slash-commands#responding_to_commands
+#[derive(Debug, Default, Deserialize, JsonSchema, Serialize)]
+pub struct SlashCommandResponse {
+    pub response_type: MessageResponseType,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blocks: Vec<MessageBlock>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replace_original: Option<bool>,
+}
+
+impl SlashCommandResponse {
+    /// A response only visible to the user who ran the command.
+    pub fn ephemeral() -> Self {
+        SlashCommandResponse {
+            response_type: MessageResponseType::Ephemeral,
+            ..Default::default()
+        }
+    }
+
+    /// A response posted into the channel the command was run in.
+    pub fn in_channel() -> Self {
+        SlashCommandResponse {
+            response_type: MessageResponseType::InChannel,
+            ..Default::default()
+        }
+    }
+
+    pub fn text(mut self, text: &str) -> Self {
+        self.text = text.to_string();
+        self
+    }
+
+    pub fn blocks(mut self, blocks: Vec<MessageBlock>) -> Self {
+        self.blocks = blocks;
+        self
+    }
+
+    pub fn replace_original(mut self, replace_original: bool) -> Self {
+        self.replace_original = Some(replace_original);
+        self
+    }
+}
+
 /// A bot command to be run and sent back to Slack.
 ///
 /// Docs: https://api.slack.com/interactivity/slash-commands#app_command_handling
@@ -593,6 +1854,31 @@ pub struct ViewResponse {
     pub error: String,
 }
 
+impl ViewResponse {
+    fn into_view(self) -> Result<Modal> {
+        if !self.ok {
+            if self.error == "trigger_expired" || self.error == "expired_trigger_id" {
+                return Err(SlackError::TriggerExpired.into());
+            }
+
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(self).to_string()
+            );
+        }
+
+        Ok(self.view)
+    }
+}
+
+/// The request body for opening a view from a raw JSON payload.
+#[derive(Debug, Clone, Deserialize, JsonSchema, Serialize)]
+pub struct OpenViewRequest {
+    pub trigger_id: String,
+    pub view: Value,
+}
+
 /// A modal to send to Slack.
 #[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
 pub struct Modal {
@@ -649,6 +1935,215 @@ pub struct FormattedMessageResponse {
     pub error: String,
 }
 
+/// The request body for an ephemeral message.
+///
+/// Docs: https://api.slack.com/methods/chat.postEphemeral
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct PostEphemeralRequest {
+    pub channel: String,
+    pub user: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blocks: Vec<MessageBlock>,
+}
+
+/// A message permalink response.
+///
+/// Docs: https://api.slack.com/methods/chat.getPermalink
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct PermalinkResponse {
+    #[serde(default)]
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub channel: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub permalink: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
+/// The request body for pinning or unpinning a message.
+///
+/// Docs: https://api.slack.com/methods/pins.add
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct PinRequest {
+    pub channel: String,
+    pub timestamp: String,
+}
+
+/// A pins list response.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct ListPinsResponse {
+    #[serde(default)]
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<PinnedItem>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
+/// An item pinned to a channel.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct PinnedItem {
+    /// "message" | "file"
+    #[serde(default, rename = "type", skip_serializing_if = "String::is_empty")]
+    pub type_: String,
+    #[serde(default)]
+    pub created: i64,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub created_by: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub channel: String,
+    #[serde(default)]
+    pub message: serde_json::Value,
+}
+
+/// The request body for adding a bookmark.
+///
+/// Docs: https://api.slack.com/methods/bookmarks.add
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct AddBookmarkRequest {
+    pub channel_id: String,
+    pub title: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub link: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub emoji: String,
+}
+
+/// A single bookmark response.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct BookmarkResponse {
+    #[serde(default)]
+    pub ok: bool,
+    #[serde(default)]
+    pub bookmark: Bookmark,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
+/// A bookmarks list response.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct ListBookmarksResponse {
+    #[serde(default)]
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bookmarks: Vec<Bookmark>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
+/// A bookmark in a channel.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct Bookmark {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub channel_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub title: String,
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        deserialize_with = "deserialize_null_string::deserialize"
+    )]
+    pub link: String,
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        deserialize_with = "deserialize_null_string::deserialize"
+    )]
+    pub emoji: String,
+    /// "link"
+    #[serde(default, rename = "type", skip_serializing_if = "String::is_empty")]
+    pub type_: String,
+    #[serde(default)]
+    pub date_created: i64,
+    #[serde(default)]
+    pub date_updated: i64,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub created_by: String,
+}
+
+/// When a reminder should fire.
+///
+/// Slack accepts either a unix timestamp, or a natural language description
+/// such as "in 15 minutes" or "every Thursday".
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, JsonSchema, Serialize)]
+#[serde(untagged)]
+pub enum ReminderTime {
+    Timestamp(i64),
+    Text(String),
+}
+
+impl From<i64> for ReminderTime {
+    fn from(ts: i64) -> Self {
+        ReminderTime::Timestamp(ts)
+    }
+}
+
+impl From<&str> for ReminderTime {
+    fn from(text: &str) -> Self {
+        ReminderTime::Text(text.to_string())
+    }
+}
+
+/// The request body for adding a reminder.
+///
+/// Docs: https://api.slack.com/methods/reminders.add
+#[derive(Debug, Clone, Deserialize, JsonSchema, Serialize)]
+pub struct AddReminderRequest {
+    pub text: String,
+    pub time: ReminderTime,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub user: String,
+}
+
+/// A single reminder response.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct ReminderResponse {
+    #[serde(default)]
+    pub ok: bool,
+    #[serde(default)]
+    pub reminder: Reminder,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
+/// A reminders list response.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct ListRemindersResponse {
+    #[serde(default)]
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reminders: Vec<Reminder>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
+/// A reminder in Slack.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct Reminder {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub creator: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub user: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub text: String,
+    #[serde(default)]
+    pub recurring: bool,
+    /// Only set for reminders that are not recurring.
+    #[serde(default)]
+    pub time: i64,
+    #[serde(default)]
+    pub complete_ts: i64,
+}
+
 /// A channel join response.
 #[derive(Debug, Clone, Deserialize, JsonSchema, Serialize)]
 pub struct JoinChannelResponse {
@@ -664,6 +2159,99 @@ pub struct JoinChannelResponse {
     pub warning: String,
 }
 
+/// A conversations info response.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct ConversationInfoResponse {
+    #[serde(default)]
+    pub ok: bool,
+    #[serde(default)]
+    pub channel: Channel,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
+impl ConversationInfoResponse {
+    fn into_channel(self, channel: &str) -> Result<Channel> {
+        if !self.ok {
+            if self.error == "channel_not_found" {
+                return Err(SlackError::ChannelNotFound(channel.to_string()).into());
+            }
+
+            bail!(
+                "status code: {}, body: {}",
+                StatusCode::OK,
+                serde_json::json!(self).to_string()
+            );
+        }
+
+        Ok(self.channel)
+    }
+}
+
+/// A response for API methods that return nothing beyond their status.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct BasicResponse {
+    #[serde(default)]
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub warning: String,
+}
+
+/// A request body for API methods that only take a channel.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct ChannelRequest {
+    pub channel: String,
+}
+
+/// The request body for removing a user from a channel.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct KickFromChannelRequest {
+    pub channel: String,
+    pub user: String,
+}
+
+/// The request body for setting a channel's topic.
+/// FROM: https://api.slack.com/methods/conversations.setTopic
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct SetChannelTopicRequest {
+    pub channel: String,
+    pub topic: String,
+}
+
+/// The request body for setting a channel's purpose.
+/// FROM: https://api.slack.com/methods/conversations.setPurpose
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct SetChannelPurposeRequest {
+    pub channel: String,
+    pub purpose: String,
+}
+
+/// The request body for opening a direct message.
+///
+/// Docs: https://api.slack.com/methods/conversations.open
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct OpenConversationRequest {
+    /// A comma-separated list of user ids.
+    pub users: String,
+}
+
+/// A conversation open response.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct OpenConversationResponse {
+    #[serde(default)]
+    pub ok: bool,
+    #[serde(default)]
+    pub channel: Channel,
+    #[serde(default)]
+    pub no_op: bool,
+    #[serde(default)]
+    pub already_open: bool,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
 /// A channel list response.
 #[derive(Debug, Clone, Deserialize, JsonSchema, Serialize)]
 pub struct ListChannelsResponse {
@@ -679,11 +2267,82 @@ pub struct ListChannelsResponse {
     pub warning: String,
 }
 
+/// A files list response.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct ListFilesResponse {
+    #[serde(default)]
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<SlackFile>,
+    #[serde(default)]
+    pub paging: Paging,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
+/// Page based pagination, used by older API methods.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct Paging {
+    #[serde(default)]
+    pub count: i64,
+    #[serde(default)]
+    pub total: i64,
+    #[serde(default)]
+    pub page: i64,
+    #[serde(default)]
+    pub pages: i64,
+}
+
+/// A file uploaded to Slack.
+/// FROM: https://api.slack.com/types/file
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct SlackFile {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    #[serde(default)]
+    pub created: i64,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub title: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub mimetype: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub filetype: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub pretty_type: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub user: String,
+    #[serde(default)]
+    pub size: i64,
+    #[serde(default)]
+    pub is_public: bool,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub url_private: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub permalink: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub channels: Vec<String>,
+}
+
 /// Response metadata.
 #[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
 pub struct ResponseMetadata {
     #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub next_cursor: String,
+    pub next_cursor: String,
+}
+
+/// A conversation members response.
+#[derive(Debug, Clone, Deserialize, JsonSchema, Serialize)]
+pub struct ConversationMembersResponse {
+    #[serde(default)]
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<String>,
+    #[serde(default)]
+    pub response_metadata: ResponseMetadata,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
 }
 
 /// A channel.
@@ -778,6 +2437,48 @@ pub struct MessageBlock {
     pub fields: Vec<MessageBlockText>,
 }
 
+impl MessageBlock {
+    /// A section block with its text laid out in two columns of `fields`,
+    /// each rendered as mrkdwn.
+    ///
+    /// Docs: https://api.slack.com/reference/block-kit/blocks#section
+    pub fn section_with_fields(fields: Vec<String>) -> Self {
+        MessageBlock {
+            block_type: MessageBlockType::Section,
+            fields: fields
+                .into_iter()
+                .map(|text| MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// A context block, for small secondary text.
+    ///
+    /// Docs: https://api.slack.com/reference/block-kit/blocks#context
+    pub fn context(elements: Vec<MessageBlockText>) -> Self {
+        MessageBlock {
+            block_type: MessageBlockType::Context,
+            elements: elements.into_iter().map(BlockOption::MessageBlockText).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// An actions block holding interactive elements like buttons.
+    ///
+    /// Docs: https://api.slack.com/reference/block-kit/blocks#actions
+    pub fn actions(buttons: Vec<ActionBlock>) -> Self {
+        MessageBlock {
+            block_type: MessageBlockType::Actions,
+            elements: buttons.into_iter().map(BlockOption::ActionBlock).collect(),
+            ..Default::default()
+        }
+    }
+}
+
 /// A Slack message block.
 ///
 /// Docs: https://api.slack.com/messaging/composing/layouts#adding-blocks
@@ -906,6 +2607,23 @@ pub struct ActionBlock {
     pub action_id: String,
 }
 
+impl ActionBlock {
+    /// A button with plain text.
+    ///
+    /// Docs: https://api.slack.com/reference/block-kit/block-elements#button
+    pub fn button(text: &str, value: &str, action_id: &str) -> Self {
+        ActionBlock {
+            text_type: MessageType::Button,
+            text: MessageBlockText {
+                text_type: MessageType::PlainText,
+                text: text.to_string(),
+            },
+            value: value.to_string(),
+            action_id: action_id.to_string(),
+        }
+    }
+}
+
 /// Message type in Slack.
 #[derive(Debug, Clone, Deserialize, JsonSchema, Serialize, Default)]
 pub enum MessageType {
@@ -1097,6 +2815,92 @@ pub struct UserInvite {
     pub resend: bool,
 }
 
+/// The data type for a user group.
+/// FROM: https://api.slack.com/types/usergroup
+#[derive(Clone, Debug, Default, JsonSchema, Serialize, Deserialize)]
+pub struct UserGroup {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub team_id: String,
+    #[serde(default)]
+    pub is_usergroup: bool,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub handle: String,
+    #[serde(default)]
+    pub is_external: bool,
+    #[serde(default)]
+    pub date_create: i64,
+    #[serde(default)]
+    pub date_update: i64,
+    #[serde(default)]
+    pub date_delete: i64,
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        deserialize_with = "deserialize_null_string::deserialize"
+    )]
+    pub created_by: String,
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        deserialize_with = "deserialize_null_string::deserialize"
+    )]
+    pub updated_by: String,
+    #[serde(
+        default,
+        skip_serializing_if = "String::is_empty",
+        deserialize_with = "deserialize_null_string::deserialize"
+    )]
+    pub deleted_by: String,
+    /// The user ids of the members, only returned when requested.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub users: Vec<String>,
+    /// Slack returns this as either a string or a number.
+    #[serde(default, deserialize_with = "deserialize_number_or_string::deserialize")]
+    pub user_count: i64,
+}
+
+#[derive(Clone, Debug, Default, JsonSchema, Serialize, Deserialize)]
+pub struct ListUserGroupsResponse {
+    #[serde(default)]
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub usergroups: Vec<UserGroup>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
+#[derive(Clone, Debug, Default, JsonSchema, Serialize, Deserialize)]
+pub struct UserGroupResponse {
+    #[serde(default)]
+    pub ok: bool,
+    #[serde(default)]
+    pub usergroup: UserGroup,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
+/// FROM: https://api.slack.com/methods/usergroups.create
+#[derive(Clone, Debug, Default, JsonSchema, Serialize, Deserialize)]
+pub struct CreateUserGroupRequest {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub handle: String,
+}
+
+/// FROM: https://api.slack.com/methods/usergroups.users.update
+#[derive(Clone, Debug, Default, JsonSchema, Serialize, Deserialize)]
+pub struct UpdateUserGroupUsersRequest {
+    pub usergroup: String,
+    /// A comma-separated list of user ids.
+    pub users: String,
+}
+
 /// The data type for an API response.
 #[derive(Clone, Debug, Default, JsonSchema, Serialize, Deserialize)]
 pub struct APIResponse {
@@ -1167,12 +2971,72 @@ pub struct BillableInfoResponse {
     pub billable_info: HashMap<String, BillableInfo>,
 }
 
+#[derive(Clone, Debug, Default, JsonSchema, Serialize, Deserialize)]
+pub struct EmojiListResponse {
+    #[serde(default)]
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub emoji: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
 #[derive(Clone, Debug, Default, JsonSchema, Serialize, Deserialize)]
 pub struct BillableInfo {
     #[serde(default)]
     pub billing_active: bool,
 }
 
+/// A user's Do Not Disturb status.
+/// FROM: https://api.slack.com/methods/dnd.info
+#[derive(Clone, Debug, Default, JsonSchema, Serialize, Deserialize)]
+pub struct DndInfo {
+    #[serde(default)]
+    pub ok: bool,
+    #[serde(default)]
+    pub dnd_enabled: bool,
+    /// Unix timestamp of the start of the next scheduled Do Not Disturb window.
+    #[serde(default)]
+    pub next_dnd_start_ts: i64,
+    /// Unix timestamp of the end of the next scheduled Do Not Disturb window.
+    #[serde(default)]
+    pub next_dnd_end_ts: i64,
+    #[serde(default)]
+    pub snooze_enabled: bool,
+    /// Unix timestamp of when the current snooze ends, only set if snooze is enabled.
+    #[serde(default)]
+    pub snooze_endtime: i64,
+    /// Seconds remaining in the current snooze, only set if snooze is enabled.
+    #[serde(default)]
+    pub snooze_remaining: i64,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
+/// A user's presence.
+/// FROM: https://api.slack.com/methods/users.getPresence
+#[derive(Clone, Debug, Default, JsonSchema, Serialize, Deserialize)]
+pub struct UserPresence {
+    #[serde(default)]
+    pub ok: bool,
+    /// "active" | "away"
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub presence: String,
+    /// The fields below are only returned when querying the authenticated user.
+    #[serde(default)]
+    pub online: bool,
+    #[serde(default)]
+    pub auto_away: bool,
+    #[serde(default)]
+    pub manual_away: bool,
+    #[serde(default)]
+    pub connection_count: i64,
+    #[serde(default)]
+    pub last_activity: i64,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
 #[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
 pub struct AccessToken {
     #[serde(default)]
@@ -1207,6 +3071,56 @@ pub struct Team {
     pub domain: String,
 }
 
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct TeamInfoResponse {
+    #[serde(default)]
+    pub ok: bool,
+    #[serde(default)]
+    pub team: TeamInfo,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
+/// The data type for a workspace.
+/// FROM: https://api.slack.com/methods/team.info
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct TeamInfo {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub domain: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub email_domain: String,
+    #[serde(default)]
+    pub icon: TeamIcon,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub enterprise_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub enterprise_name: String,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct TeamIcon {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub image_34: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub image_44: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub image_68: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub image_88: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub image_102: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub image_132: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub image_230: String,
+    #[serde(default)]
+    pub image_default: bool,
+}
+
 #[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
 pub struct IncomingWebhook {
     #[serde(default, skip_serializing_if = "String::is_empty")]
@@ -1340,3 +3254,628 @@ pub mod deserialize_null_string {
         Ok(s)
     }
 }
+
+pub mod deserialize_number_or_string {
+    use serde::{self, Deserialize, Deserializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum NumberOrString {
+        Number(i64),
+        String(String),
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<i64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let n = match NumberOrString::deserialize(deserializer) {
+            Ok(NumberOrString::Number(n)) => n,
+            Ok(NumberOrString::String(s)) => s.parse().unwrap_or_default(),
+            Err(_) => 0,
+        };
+
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dnd_info_deserialize() {
+        let body = r#"{
+            "ok": true,
+            "dnd_enabled": true,
+            "next_dnd_start_ts": 1450416600,
+            "next_dnd_end_ts": 1450452600,
+            "snooze_enabled": true,
+            "snooze_endtime": 1450416600,
+            "snooze_remaining": 1196
+        }"#;
+
+        let info: DndInfo = serde_json::from_str(body).unwrap();
+
+        assert!(info.ok);
+        assert!(info.dnd_enabled);
+        assert_eq!(info.next_dnd_start_ts, 1450416600);
+        assert_eq!(info.next_dnd_end_ts, 1450452600);
+        assert!(info.snooze_enabled);
+        assert_eq!(info.snooze_remaining, 1196);
+    }
+
+    #[test]
+    fn test_user_presence_deserialize() {
+        let body = r#"{
+            "ok": true,
+            "presence": "active",
+            "online": true,
+            "auto_away": false,
+            "manual_away": false,
+            "connection_count": 1,
+            "last_activity": 1419027078
+        }"#;
+
+        let presence: UserPresence = serde_json::from_str(body).unwrap();
+
+        assert!(presence.ok);
+        assert_eq!(presence.presence, "active");
+        assert!(presence.online);
+        assert_eq!(presence.connection_count, 1);
+
+        // Other users only return the presence.
+        let presence: UserPresence = serde_json::from_str(r#"{"ok": true, "presence": "away"}"#).unwrap();
+        assert_eq!(presence.presence, "away");
+        assert!(!presence.online);
+    }
+
+    #[test]
+    fn test_team_info_deserialize() {
+        let body = r#"{
+            "ok": true,
+            "team": {
+                "id": "T12345",
+                "name": "My Team",
+                "domain": "example",
+                "email_domain": "example.com",
+                "icon": {
+                    "image_34": "https://example.com/icon_34.png",
+                    "image_44": "https://example.com/icon_44.png",
+                    "image_default": true
+                },
+                "enterprise_id": "E1234A12AB",
+                "enterprise_name": "Umbrella Corporation"
+            }
+        }"#;
+
+        let r: TeamInfoResponse = serde_json::from_str(body).unwrap();
+
+        assert!(r.ok);
+        assert_eq!(r.team.id, "T12345");
+        assert_eq!(r.team.domain, "example");
+        assert_eq!(r.team.icon.image_34, "https://example.com/icon_34.png");
+        assert!(r.team.icon.image_default);
+    }
+
+    #[test]
+    fn test_conversation_members_deserialize() {
+        let body = r#"{
+            "ok": true,
+            "members": ["U023BECGF", "U061F7AUR", "W012A3CDE"],
+            "response_metadata": {
+                "next_cursor": "e3VzZXJfaWQ6IFcxMjM0NTY3fQ=="
+            }
+        }"#;
+
+        let r: ConversationMembersResponse = serde_json::from_str(body).unwrap();
+
+        assert!(r.ok);
+        assert_eq!(r.members.len(), 3);
+        assert_eq!(r.response_metadata.next_cursor, "e3VzZXJfaWQ6IFcxMjM0NTY3fQ==");
+
+        // The last page has an empty cursor.
+        let body = r#"{"ok": true, "members": ["U0123"], "response_metadata": {"next_cursor": ""}}"#;
+        let r: ConversationMembersResponse = serde_json::from_str(body).unwrap();
+        assert!(r.response_metadata.next_cursor.is_empty());
+    }
+
+    #[test]
+    fn test_channel_management_request_bodies() {
+        let topic = serde_json::to_value(SetChannelTopicRequest {
+            channel: "C1234567890".to_string(),
+            topic: "Apply topically for best effects".to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            topic,
+            serde_json::json!({"channel": "C1234567890", "topic": "Apply topically for best effects"})
+        );
+
+        let purpose = serde_json::to_value(SetChannelPurposeRequest {
+            channel: "C1234567890".to_string(),
+            purpose: "This is the purpose".to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            purpose,
+            serde_json::json!({"channel": "C1234567890", "purpose": "This is the purpose"})
+        );
+
+        let archive = serde_json::to_value(ChannelRequest {
+            channel: "C1234567890".to_string(),
+        })
+        .unwrap();
+        assert_eq!(archive, serde_json::json!({"channel": "C1234567890"}));
+    }
+
+    #[test]
+    fn test_basic_response_error_deserialize() {
+        let r: BasicResponse = serde_json::from_str(r#"{"ok": false, "error": "already_archived"}"#).unwrap();
+
+        assert!(!r.ok);
+        assert_eq!(r.error, "already_archived");
+    }
+
+    #[test]
+    fn test_post_ephemeral_request_body() {
+        let body = serde_json::to_value(PostEphemeralRequest {
+            channel: "C1234567890".to_string(),
+            user: "U0BPQUNTA".to_string(),
+            text: "Only you can see this".to_string(),
+            blocks: vec![],
+        })
+        .unwrap();
+
+        assert_eq!(body["channel"], "C1234567890");
+        assert_eq!(body["user"], "U0BPQUNTA");
+        assert_eq!(body["text"], "Only you can see this");
+        assert!(body.get("blocks").is_none());
+    }
+
+    #[test]
+    fn test_escape_mrkdwn() {
+        assert_eq!(
+            escape_mrkdwn("Tom & Jerry <3 > cats, ping <@U123>"),
+            "Tom &amp; Jerry &lt;3 &gt; cats, ping &lt;@U123&gt;"
+        );
+
+        // Already escaped text is escaped again, not passed through.
+        assert_eq!(escape_mrkdwn("&lt;"), "&amp;lt;");
+        assert_eq!(escape_mrkdwn("plain text"), "plain text");
+    }
+
+    #[test]
+    fn test_permalink_response_deserialize() {
+        let body = r#"{
+            "ok": true,
+            "channel": "C1H9RESGA",
+            "permalink": "https://ghostbusters.slack.com/archives/C1H9RESGA/p135854651500008"
+        }"#;
+
+        let r: PermalinkResponse = serde_json::from_str(body).unwrap();
+
+        assert!(r.ok);
+        assert_eq!(r.channel, "C1H9RESGA");
+        assert_eq!(
+            r.permalink,
+            "https://ghostbusters.slack.com/archives/C1H9RESGA/p135854651500008"
+        );
+    }
+
+    #[test]
+    fn test_open_conversation() {
+        let body = serde_json::to_value(OpenConversationRequest {
+            users: ["W123456", "U234567"].join(","),
+        })
+        .unwrap();
+        assert_eq!(body, serde_json::json!({"users": "W123456,U234567"}));
+
+        let r: OpenConversationResponse = serde_json::from_str(
+            r#"{
+                "ok": true,
+                "no_op": true,
+                "already_open": true,
+                "channel": {
+                    "id": "D069C7QFK"
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert!(r.ok);
+        assert!(r.already_open);
+        assert_eq!(r.channel.id, "D069C7QFK");
+    }
+
+    #[test]
+    fn test_usergroup_deserialize() {
+        let body = r#"{
+            "ok": true,
+            "usergroups": [
+                {
+                    "id": "S0614TZR7",
+                    "team_id": "T060RNRCH",
+                    "is_usergroup": true,
+                    "name": "Team Admins",
+                    "description": "A group of all Administrators on your team.",
+                    "handle": "admins",
+                    "is_external": false,
+                    "date_create": 1446598059,
+                    "date_update": 1446670362,
+                    "date_delete": 0,
+                    "auto_type": "admin",
+                    "created_by": "USLACKBOT",
+                    "updated_by": "U060RNRCZ",
+                    "deleted_by": null,
+                    "prefs": {
+                        "channels": [],
+                        "groups": []
+                    },
+                    "users": ["U060R4BJ4", "W123A4BC5"],
+                    "user_count": "2"
+                }
+            ]
+        }"#;
+
+        let r: ListUserGroupsResponse = serde_json::from_str(body).unwrap();
+
+        assert!(r.ok);
+        assert_eq!(r.usergroups.len(), 1);
+        let group = &r.usergroups[0];
+        assert_eq!(group.handle, "admins");
+        assert_eq!(group.users, vec!["U060R4BJ4", "W123A4BC5"]);
+        assert_eq!(group.user_count, 2);
+    }
+
+    #[test]
+    fn test_update_usergroup_users() {
+        let body = serde_json::to_value(UpdateUserGroupUsersRequest {
+            usergroup: "S0604QSJC".to_string(),
+            users: ["U060R4BJ4", "U060RNRCZ"].join(","),
+        })
+        .unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({"usergroup": "S0604QSJC", "users": "U060R4BJ4,U060RNRCZ"})
+        );
+
+        let r: UserGroupResponse = serde_json::from_str(
+            r#"{
+                "ok": true,
+                "usergroup": {
+                    "id": "S0604QSJC",
+                    "handle": "marketing-team",
+                    "users": ["U060R4BJ4", "U060RNRCZ"],
+                    "user_count": 2
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(r.usergroup.users.len(), 2);
+        assert_eq!(r.usergroup.user_count, 2);
+    }
+
+    #[test]
+    fn test_pin_request_body() {
+        let body = serde_json::to_value(PinRequest {
+            channel: "C1234567890".to_string(),
+            timestamp: "1234567890.123456".to_string(),
+        })
+        .unwrap();
+
+        assert_eq!(
+            body,
+            serde_json::json!({"channel": "C1234567890", "timestamp": "1234567890.123456"})
+        );
+    }
+
+    #[test]
+    fn test_emoji_list_deserialize() {
+        let body = r#"{
+            "ok": true,
+            "emoji": {
+                "bowtie": "https://my.slack.com/emoji/bowtie/46ec6f2bb0.png",
+                "squirrel": "https://my.slack.com/emoji/squirrel/f35f40c0e0.png",
+                "shipit": "alias:squirrel"
+            }
+        }"#;
+
+        let r: EmojiListResponse = serde_json::from_str(body).unwrap();
+
+        assert!(r.ok);
+        assert_eq!(r.emoji.len(), 3);
+        assert_eq!(
+            r.emoji.get("bowtie").unwrap(),
+            "https://my.slack.com/emoji/bowtie/46ec6f2bb0.png"
+        );
+        assert_eq!(r.emoji.get("shipit").unwrap(), "alias:squirrel");
+    }
+
+    #[test]
+    fn test_open_view_request_body() {
+        let view = serde_json::json!({
+            "type": "modal",
+            "title": {"type": "plain_text", "text": "Approve"},
+            "blocks": []
+        });
+
+        let body = serde_json::to_value(OpenViewRequest {
+            trigger_id: "12345.98765.abcd2358fdea".to_string(),
+            view: view.clone(),
+        })
+        .unwrap();
+
+        assert_eq!(body["trigger_id"], "12345.98765.abcd2358fdea");
+        assert_eq!(body["view"], view);
+    }
+
+    #[test]
+    fn test_view_response_trigger_expired() {
+        for error in ["trigger_expired", "expired_trigger_id"] {
+            let r: ViewResponse = serde_json::from_value(serde_json::json!({"ok": false, "error": error})).unwrap();
+
+            let err = r.into_view().unwrap_err();
+            assert_eq!(err.downcast_ref::<SlackError>(), Some(&SlackError::TriggerExpired));
+        }
+
+        let r: ViewResponse =
+            serde_json::from_value(serde_json::json!({"ok": false, "error": "invalid_arguments"})).unwrap();
+        assert!(r.into_view().unwrap_err().downcast_ref::<SlackError>().is_none());
+    }
+
+    #[test]
+    fn test_slash_command_response_ephemeral_with_blocks() {
+        let resp = SlashCommandResponse::ephemeral()
+            .text("Deploying...")
+            .blocks(vec![MessageBlock {
+                block_type: MessageBlockType::Section,
+                text: Some(MessageBlockText {
+                    text_type: MessageType::Markdown,
+                    text: "*Deploying* `cio`".to_string(),
+                }),
+                ..Default::default()
+            }]);
+
+        let body = serde_json::to_value(&resp).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "response_type": "ephemeral",
+                "text": "Deploying...",
+                "blocks": [{
+                    "type": "section",
+                    "text": {"type": "mrkdwn", "text": "*Deploying* `cio`"}
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn test_deserialize_bookmark() {
+        let r: ListBookmarksResponse = serde_json::from_str(
+            r#"{
+                "ok": true,
+                "bookmarks": [{
+                    "id": "Bk01ABCDEF12",
+                    "channel_id": "C0123456789",
+                    "title": "Dashboard",
+                    "link": "https://grafana.example.com/d/abc",
+                    "emoji": null,
+                    "icon_url": null,
+                    "type": "link",
+                    "entity_id": null,
+                    "date_created": 1650000000,
+                    "date_updated": 0,
+                    "rank": "U",
+                    "last_updated_by_user_id": "U0123456789",
+                    "last_updated_by_team_id": "T0123456789",
+                    "shortcut_id": null,
+                    "app_id": null
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        assert!(r.ok);
+        assert_eq!(r.bookmarks.len(), 1);
+        let b = &r.bookmarks[0];
+        assert_eq!(b.title, "Dashboard");
+        assert_eq!(b.link, "https://grafana.example.com/d/abc");
+        assert_eq!(b.emoji, "");
+        assert_eq!(b.type_, "link");
+        assert_eq!(b.date_created, 1650000000);
+
+        let body = serde_json::to_value(AddBookmarkRequest {
+            channel_id: "C0123456789".to_string(),
+            title: "Dashboard".to_string(),
+            type_: "link".to_string(),
+            link: "https://grafana.example.com/d/abc".to_string(),
+            emoji: ":chart_with_upwards_trend:".to_string(),
+        })
+        .unwrap();
+        assert_eq!(body["type"], "link");
+        assert_eq!(body["emoji"], ":chart_with_upwards_trend:");
+    }
+
+    #[test]
+    fn test_reminder_time_serialize() {
+        let body = serde_json::to_value(AddReminderRequest {
+            text: "Review the RFD".to_string(),
+            time: ReminderTime::from(1602288000),
+            user: String::new(),
+        })
+        .unwrap();
+        assert_eq!(body, serde_json::json!({"text": "Review the RFD", "time": 1602288000}));
+
+        let body = serde_json::to_value(AddReminderRequest {
+            text: "Review the RFD".to_string(),
+            time: ReminderTime::from("in 15 minutes"),
+            user: "U18888888".to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({"text": "Review the RFD", "time": "in 15 minutes", "user": "U18888888"})
+        );
+    }
+
+    #[test]
+    fn test_deserialize_conversation_info() {
+        let r: ConversationInfoResponse = serde_json::from_str(
+            r#"{
+                "ok": true,
+                "channel": {
+                    "id": "C012AB3CD",
+                    "name": "general",
+                    "is_channel": true,
+                    "is_group": false,
+                    "is_im": false,
+                    "created": 1449252889,
+                    "creator": "W012A3BCD",
+                    "is_archived": false,
+                    "is_general": true,
+                    "unlinked": 0,
+                    "name_normalized": "general",
+                    "is_read_only": false,
+                    "is_shared": false,
+                    "is_member": true,
+                    "is_private": false,
+                    "is_mpim": false,
+                    "last_read": "1502126650.228446",
+                    "topic": {"value": "For public discussion of generalities", "creator": "W012A3BCD", "last_set": 1449709364},
+                    "purpose": {"value": "This part of the workspace is for fun.", "creator": "", "last_set": 0},
+                    "previous_names": ["specifics", "abstractions"],
+                    "locale": "en-US"
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let channel = r.into_channel("C012AB3CD").unwrap();
+        assert_eq!(channel.id, "C012AB3CD");
+        assert_eq!(channel.name, "general");
+        assert!(channel.is_general);
+        assert_eq!(channel.topic.value, "For public discussion of generalities");
+        assert_eq!(channel.previous_names, vec!["specifics", "abstractions"]);
+
+        let r: ConversationInfoResponse =
+            serde_json::from_value(serde_json::json!({"ok": false, "error": "channel_not_found"})).unwrap();
+        let err = r.into_channel("C0NOTREAL").unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<SlackError>(),
+            Some(&SlackError::ChannelNotFound("C0NOTREAL".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_format_date() {
+        let ts = DateTime::parse_from_rfc3339("2014-02-18T14:39:42Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(
+            format_date(ts, "{date_num} {time_secs}", None, "2014-02-18 6:39:42 AM PST"),
+            "<!date^1392734382^{date_num} {time_secs}|2014-02-18 6:39:42 AM PST>"
+        );
+
+        assert_eq!(
+            format_date(ts, "{date_short}", Some("https://example.com/"), "Feb 18, 2014 PST"),
+            "<!date^1392734382^{date_short}^https://example.com/|Feb 18, 2014 PST>"
+        );
+
+        // The fallback cannot break out of the token.
+        assert_eq!(
+            format_date(ts, "{date}", None, "<soon>"),
+            "<!date^1392734382^{date}|&lt;soon&gt;>"
+        );
+    }
+
+    #[test]
+    fn test_message_block_builders() {
+        let section =
+            MessageBlock::section_with_fields(vec!["*Status:*\nHealthy".to_string(), "*Uptime:*\n99.9%".to_string()]);
+        assert_eq!(
+            serde_json::to_value(&section).unwrap(),
+            serde_json::json!({
+                "type": "section",
+                "fields": [
+                    {"type": "mrkdwn", "text": "*Status:*\nHealthy"},
+                    {"type": "mrkdwn", "text": "*Uptime:*\n99.9%"}
+                ]
+            })
+        );
+
+        let context = MessageBlock::context(vec![MessageBlockText {
+            text_type: MessageType::Markdown,
+            text: "Last updated by <@U123>".to_string(),
+        }]);
+        assert_eq!(
+            serde_json::to_value(&context).unwrap(),
+            serde_json::json!({
+                "type": "context",
+                "elements": [{"type": "mrkdwn", "text": "Last updated by <@U123>"}]
+            })
+        );
+
+        let actions = MessageBlock::actions(vec![
+            ActionBlock::button("Approve", "approve", "approve_request"),
+            ActionBlock::button("Deny", "deny", "deny_request"),
+        ]);
+        assert_eq!(
+            serde_json::to_value(&actions).unwrap(),
+            serde_json::json!({
+                "type": "actions",
+                "elements": [
+                    {"type": "button", "text": {"type": "plain_text", "text": "Approve"}, "value": "approve", "action_id": "approve_request"},
+                    {"type": "button", "text": {"type": "plain_text", "text": "Deny"}, "value": "deny", "action_id": "deny_request"}
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_deserialize_files_list() {
+        let r: ListFilesResponse = serde_json::from_str(
+            r#"{
+                "ok": true,
+                "files": [{
+                    "id": "F0S43PZDF",
+                    "created": 1531763342,
+                    "timestamp": 1531763342,
+                    "name": "tedair.gif",
+                    "title": "tedair.gif",
+                    "mimetype": "image/gif",
+                    "filetype": "gif",
+                    "pretty_type": "GIF",
+                    "user": "U061F7AUR",
+                    "editable": false,
+                    "size": 137531,
+                    "mode": "hosted",
+                    "is_external": false,
+                    "external_type": "",
+                    "is_public": true,
+                    "public_url_shared": false,
+                    "url_private": "https://files.slack.com/files-pri/T061EG9R6-F0S43PZDF/tedair.gif",
+                    "permalink": "https://example.slack.com/files/U061F7AUR/F0S43PZDF/tedair.gif",
+                    "channels": ["C0T8SE4AU"],
+                    "groups": [],
+                    "ims": [],
+                    "comments_count": 0
+                }],
+                "paging": {"count": 100, "total": 1, "page": 1, "pages": 1}
+            }"#,
+        )
+        .unwrap();
+
+        assert!(r.ok);
+        assert_eq!(r.files.len(), 1);
+        let f = &r.files[0];
+        assert_eq!(f.id, "F0S43PZDF");
+        assert_eq!(f.size, 137531);
+        assert_eq!(f.channels, vec!["C0T8SE4AU"]);
+        assert_eq!(r.paging.page, 1);
+        assert_eq!(r.paging.pages, 1);
+    }
+}
@@ -0,0 +1,277 @@
+use httpmock::MockServer;
+use reqwest::Url;
+use serde_json::json;
+
+use slack_chat_api::{FormattedMessage, Slack, UserInvite};
+
+fn slack(server: &MockServer) -> Slack {
+    Slack::new("client_id", "client_secret", "T123", "", "xoxb-token", "xoxp-token")
+        .base_url(Url::parse(&server.url("/api/")).unwrap())
+}
+
+#[tokio::test]
+async fn admin_invite_users_test() {
+    let server = MockServer::start();
+    let mut mocks = Vec::new();
+    for email in ["alice@example.com", "bob@example.com"] {
+        mocks.push(server.mock(|when, then| {
+            when.method("POST")
+                .path("/api/admin.users.invite")
+                .json_body_partial(json!({ "email": email }).to_string());
+            then.status(200).json_body(json!({"ok": true}));
+        }));
+    }
+    let failing = server.mock(|when, then| {
+        when.method("POST")
+            .path("/api/admin.users.invite")
+            .json_body_partial(json!({"email": "carol@example.com"}).to_string());
+        then.status(200)
+            .json_body(json!({"ok": false, "error": "already_in_team"}));
+    });
+
+    let invites: Vec<UserInvite> = ["alice@example.com", "carol@example.com", "bob@example.com"]
+        .iter()
+        .map(|email| UserInvite {
+            email: email.to_string(),
+            team_id: "T123".to_string(),
+            channel_ids: vec!["C123".to_string()],
+            ..Default::default()
+        })
+        .collect();
+
+    let results = slack(&server).admin_invite_users(&invites, 2).await;
+
+    let emails: Vec<&str> = results.iter().map(|(email, _)| email.as_str()).collect();
+    assert_eq!(
+        emails,
+        vec!["alice@example.com", "carol@example.com", "bob@example.com"]
+    );
+    assert!(results[0].1.is_ok());
+    assert!(results[1]
+        .1
+        .as_ref()
+        .unwrap_err()
+        .to_string()
+        .contains("already_in_team"));
+    assert!(results[2].1.is_ok());
+
+    for mock in mocks {
+        mock.assert();
+    }
+    failing.assert();
+}
+
+#[tokio::test]
+async fn kick_from_channel_test() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method("POST")
+            .path("/api/conversations.kick")
+            .header("authorization", "Bearer xoxb-token")
+            .json_body(json!({"channel": "C123", "user": "U456"}));
+        then.status(200).json_body(json!({"ok": true}));
+    });
+
+    slack(&server).kick_from_channel("C123", "U456").await.unwrap();
+
+    mock.assert();
+}
+
+#[tokio::test]
+async fn leave_channel_test() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method("POST")
+            .path("/api/conversations.leave")
+            .json_body(json!({"channel": "C123"}));
+        then.status(200)
+            .json_body(json!({"ok": false, "error": "not_in_channel"}));
+    });
+
+    // Not being in the channel is fine.
+    slack(&server).leave_channel("C123").await.unwrap();
+
+    mock.assert();
+}
+
+#[tokio::test]
+async fn token_kind_test() {
+    let server = MockServer::start();
+    let admin = server.mock(|when, then| {
+        when.method("POST")
+            .path("/api/admin.users.remove")
+            .header("authorization", "Bearer xoxp-token");
+        then.status(200).json_body(json!({"ok": true}));
+    });
+    let post = server.mock(|when, then| {
+        when.method("POST")
+            .path("/api/chat.postMessage")
+            .header("authorization", "Bearer xoxb-token");
+        then.status(200)
+            .json_body(json!({"ok": true, "channel": "C123", "ts": "1503435956.000247"}));
+    });
+
+    let slack = slack(&server);
+    slack.remove_user("U456").await.unwrap();
+    slack
+        .post_message(&FormattedMessage {
+            channel: "C123".to_string(),
+            blocks: Default::default(),
+            attachments: Default::default(),
+        })
+        .await
+        .unwrap();
+
+    admin.assert();
+    post.assert();
+}
+
+#[tokio::test]
+async fn execute_with_retry_test() {
+    let server = MockServer::start_async().await;
+    let mut limited = server
+        .mock_async(|when, then| {
+            when.method("GET").path("/api/team.info");
+            then.status(429).header("Retry-After", "1");
+        })
+        .await;
+
+    let slack = slack(&server);
+    let url = server.url("/api/team.info");
+    let retry = slack.execute_with_retry(|| Ok(reqwest::Client::new().get(&url).build()?));
+
+    // Once we have been rate limited, let the next attempt through.
+    let swap = async {
+        while limited.hits_async().await < 1 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        limited.delete_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method("GET").path("/api/team.info");
+                then.status(200).json_body(json!({"ok": true}));
+            })
+            .await
+    };
+
+    let (resp, ok) = tokio::join!(retry, swap);
+
+    assert_eq!(resp.unwrap().status(), reqwest::StatusCode::OK);
+    ok.assert_hits(1);
+}
+
+#[tokio::test]
+async fn ensure_channel_metadata_test() {
+    let server = MockServer::start();
+    let info = server.mock(|when, then| {
+        when.method("GET")
+            .path("/api/conversations.info")
+            .query_param("channel", "C123");
+        then.status(200).json_body(json!({
+            "ok": true,
+            "channel": {
+                "id": "C123",
+                "name": "project",
+                "topic": {"value": "Shipping the thing", "creator": "U123", "last_set": 1},
+                "purpose": {"value": "", "creator": "", "last_set": 0}
+            }
+        }));
+    });
+    let set_topic = server.mock(|when, then| {
+        when.method("POST").path("/api/conversations.setTopic");
+        then.status(200).json_body(json!({"ok": true}));
+    });
+    let set_purpose = server.mock(|when, then| {
+        when.method("POST")
+            .path("/api/conversations.setPurpose")
+            .json_body_partial(json!({"channel": "C123", "purpose": "Where we ship the thing"}).to_string());
+        then.status(200).json_body(json!({"ok": true}));
+    });
+    let list = server.mock(|when, then| {
+        when.method("GET")
+            .path("/api/bookmarks.list")
+            .query_param("channel_id", "C123");
+        then.status(200).json_body(json!({
+            "ok": true,
+            "bookmarks": [{"id": "Bk1", "channel_id": "C123", "title": "RFD", "link": "https://rfd.example.com/1", "type": "link"}]
+        }));
+    });
+    let add = server.mock(|when, then| {
+        when.method("POST")
+            .path("/api/bookmarks.add")
+            .json_body_partial(json!({"link": "https://github.com/oxidecomputer/thing"}).to_string());
+        then.status(200).json_body(json!({
+            "ok": true,
+            "bookmark": {"id": "Bk2", "channel_id": "C123", "title": "Repo", "link": "https://github.com/oxidecomputer/thing", "type": "link"}
+        }));
+    });
+
+    slack(&server)
+        .ensure_channel_metadata(
+            "C123",
+            Some("Shipping the thing"),
+            Some("Where we ship the thing"),
+            &[
+                ("RFD".to_string(), "https://rfd.example.com/1".to_string()),
+                ("Repo".to_string(), "https://github.com/oxidecomputer/thing".to_string()),
+            ],
+        )
+        .await
+        .unwrap();
+
+    info.assert();
+    // The topic is unchanged so it is not set again.
+    set_topic.assert_hits(0);
+    set_purpose.assert();
+    list.assert();
+    add.assert();
+}
+
+#[tokio::test]
+async fn invite_user_ignores_ok_test() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method("POST").path("/api/admin.users.invite");
+        then.status(200)
+            .json_body(json!({"ok": false, "error": "already_in_team"}));
+    });
+
+    // `invite_user` only checks the status code, as it always has.
+    slack(&server)
+        .invite_user(UserInvite {
+            email: "carol@example.com".to_string(),
+            team_id: "T123".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    mock.assert();
+}
+
+#[tokio::test]
+async fn pin_message_channel_name_test() {
+    let server = MockServer::start();
+    let list = server.mock(|when, then| {
+        when.method("GET").path("/api/conversations.list");
+        then.status(200).json_body(json!({
+            "ok": true,
+            "channels": [{"id": "C123", "name": "project"}],
+            "response_metadata": {"next_cursor": ""}
+        }));
+    });
+    let pin = server.mock(|when, then| {
+        when.method("POST")
+            .path("/api/pins.add")
+            .json_body_partial(json!({"channel": "C123", "timestamp": "1503435956.000247"}).to_string());
+        then.status(200).json_body(json!({"ok": true}));
+    });
+
+    slack(&server)
+        .pin_message("#project", "1503435956.000247")
+        .await
+        .unwrap();
+
+    list.assert();
+    pin.assert();
+}